@@ -175,8 +175,22 @@ impl<T:Clone+RealNumber> Exp for Complex<T> {
 }
 
 impl<R:Clone+RealNumber> Complex<R> {
-    fn expi(self) -> Self {
-        Self::exp(Self::i() * self)
+    // for small arguments sinh is evaluated by its power series, (exp(y)-exp(-y))/2
+    // would cancel, e.g. for the tiny imaginary parts of complex step differentiation
+    fn sinh_cosh(y:R) -> (R,R) {
+        let e=y.clone().exp();
+        let ei=R::one().try_div(e.clone()).unwrap();
+        let cosh=(e.clone()+ei.clone()).div2();
+        let sinh=if y.clone().into_norm().into_signed() < R::from_f64(0.1) {
+            // y*(1+y^2/3!+y^4/5!+y^6/7!+y^8/9!)
+            let y2=y.clone()*y.clone();
+            let series=[72.0, 42.0, 20.0, 6.0].into_iter()
+                                              .fold(R::one(),|acc,d|R::one()+y2.clone()*acc.try_div(R::from_f64(d)).unwrap());
+            y*series
+        } else {
+            (e-ei).div2()
+        };
+        (sinh, cosh)
     }
 }
 
@@ -404,13 +418,18 @@ impl<R:RealNumber> Tolerance for Complex<R> {
 impl<R:Clone+RealNumber> TrigonometricFunctions for Complex<R> {
     type Output=Self;
 
+    // sin(x+iy)=sin(x)*cosh(y)+i*cos(x)*sinh(y)
     fn sin(self) -> Self::Output {
-        let sini=(Self::expi(self.clone())-Self::expi(-self.clone())).div2();
-        <Self as TryDiv>::try_div(sini, Self::i()).unwrap()
+        let [x, y]=self.into_real_imag();
+        let (sinh, cosh)=Self::sinh_cosh(y);
+        Self::new(x.clone().sin()*cosh, x.cos()*sinh)
     }
 
+    // cos(x+iy)=cos(x)*cosh(y)-i*sin(x)*sinh(y)
     fn cos(self) -> Self::Output {
-        (Self::expi(self.clone())+Self::expi(-self.clone())).div2()
+        let [x, y]=self.into_real_imag();
+        let (sinh, cosh)=Self::sinh_cosh(y);
+        Self::new(x.clone().cos()*cosh, -x.sin()*sinh)
     }
 
     fn tan(self) -> Result<Self::Output,DivError> {
@@ -459,6 +478,17 @@ impl<R:Clone+RealNumber> Sinc for Complex<R> {
     }
 }
 
+#[test]
+fn test_complex_step() {
+    // the imaginary part of sin(x+ih) is cos(x)*h up to rounding
+    let h=1e-20;
+    let s=c64::new(0.7, h).sin();
+    assert!((s.imag()/h - 0.7_f64.cos()).abs() < 1e-15);
+    assert!((s.real() - 0.7_f64.sin()).abs() < 1e-15);
+    let c=c64::new(0.7, h).cos();
+    assert!((c.imag()/h + 0.7_f64.sin()).abs() < 1e-15);
+}
+
 #[test]
 fn test_roots() {
     let a=c64::from(4.0);
@@ -0,0 +1,570 @@
+// dual numbers re+eps*ε with ε*ε=0. evaluating a function which is generic in the scalar
+// at x+ε yields f(x)+f'(x)*ε, i.e. the derivative exact up to rounding (forward mode
+// automatic differentiation)
+use std::cmp::Ordering;
+use std::convert::Infallible;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+use std::fmt::Display;
+use algebra_traits::*;
+use container_traits::{AnyFromParameters, ContainerConstructError, IntoParameters, LenTooSmallError, LinearContainerConstructError};
+use container_traits::for_static::TryFromIterator;
+use num_traits::{Zero, One};
+
+#[derive(Clone,
+         Debug,
+         PartialEq)]
+pub struct Dual<F> {
+    re: F,
+    eps: F,
+}
+
+impl<F> Dual<F> {
+    pub fn new(re:F, eps:F) -> Self {
+        Self { re, eps }
+    }
+
+    pub fn re(&self) -> &F {
+        &self.re
+    }
+
+    pub fn eps(&self) -> &F {
+        &self.eps
+    }
+
+    pub fn into_re_eps(self) -> [F;2] {
+        [self.re, self.eps]
+    }
+}
+
+impl<F:Zero> Dual<F> {
+    // a value which does not depend on the variable
+    pub fn constant(re:F) -> Self {
+        Self::new(re, F::zero())
+    }
+}
+
+impl<F:Zero+One> Dual<F> {
+    // the variable with respect to which is differentiated
+    pub fn variable(re:F) -> Self {
+        Self::new(re, F::one())
+    }
+}
+
+impl<F:Display> Display for Dual<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width=f.width().unwrap_or(6);
+        let precision=f.precision().unwrap_or(4);
+        write!(f, "{:+width$.precision$}",  self.re,  width=width, precision=precision)?;
+        write!(f, "{:+width$.precision$}ε", self.eps, width=width, precision=precision)
+    }
+}
+
+impl<F:Add<Output=F>> Add for Dual<F> {
+    type Output=Self;
+    fn add(self, rhs:Self) -> Self {
+        Self::new(self.re+rhs.re, self.eps+rhs.eps)
+    }
+}
+
+impl<F:Sub<Output=F>> Sub for Dual<F> {
+    type Output=Self;
+    fn sub(self, rhs:Self) -> Self {
+        Self::new(self.re-rhs.re, self.eps-rhs.eps)
+    }
+}
+
+impl<F:Neg<Output=F>> Neg for Dual<F> {
+    type Output=Self;
+    fn neg(self) -> Self {
+        Self::new(-self.re, -self.eps)
+    }
+}
+
+impl<F:Clone+Add<Output=F>+Mul<Output=F>> Mul for Dual<F> {
+    type Output=Self;
+    fn mul(self, rhs:Self) -> Self {
+        Self::new(self.re.clone()*rhs.re.clone(), self.re*rhs.eps+self.eps*rhs.re)
+    }
+}
+
+impl<F:Clone+Add<Output=F>> AddAssign for Dual<F> {
+    fn add_assign(&mut self, rhs:Self) {
+        *self=self.clone()+rhs;
+    }
+}
+
+impl<F:Clone+Sub<Output=F>> SubAssign for Dual<F> {
+    fn sub_assign(&mut self, rhs:Self) {
+        *self=self.clone()-rhs;
+    }
+}
+
+macro_rules! try_add_sub {
+    ($tr:ident, $fn:ident) => {
+        paste::paste!(
+        impl<F:RealNumber> [<Try $tr>] for Dual<F> {
+            type Output=Self;
+            type Error=Infallible;
+            fn [<is_ $fn able_by>](&self, _:&Self) -> Result<(),Infallible> {
+                Ok(())
+            }
+
+            fn [<try_ $fn>](self, rhs:Self) -> Result<Self,Infallible> {
+                Ok(<Self as $tr>::$fn(self, rhs))
+            }
+        });
+    };
+}
+try_add_sub!(Add, add);
+try_add_sub!(Sub, sub);
+
+impl<F:Zero> Zero for Dual<F> {
+    fn zero() -> Self {
+        Self::new(F::zero(), F::zero())
+    }
+
+    fn is_zero(&self) -> bool {
+        self.re.is_zero() && self.eps.is_zero()
+    }
+}
+
+impl<F:Clone+Zero+One> One for Dual<F> {
+    fn one() -> Self {
+        Self::new(F::one(), F::zero())
+    }
+}
+
+// a dual number is invertible if and only if its real part is
+impl<F:RealNumber> IsAZero for Dual<F> {
+    fn is_a_zero(&self) -> bool {
+        self.re.is_a_zero()
+    }
+}
+
+impl<F:Zero> Origin for Dual<F> {
+    fn origin() -> Self {
+        Self::zero()
+    }
+}
+
+impl<F:RealNumber> Conjugate for Dual<F> {
+    type Output=Self;
+
+    fn into_conjugate(self) -> Self {
+        self
+    }
+
+    fn are_conjugates(&self, rhs:&Self) -> bool {
+        self == rhs
+    }
+}
+
+impl<F:RealNumber> Pow2 for Dual<F> {
+    type Output=Self;
+    fn pow2(self) -> Self {
+        self.clone() * self
+    }
+}
+
+impl<F:RealNumber> TryInv for Dual<F> {
+    type Output=Self;
+    type Error=InvError;
+    fn is_invertible(&self) -> Result<(),InvError> {
+        DivisionByZeroError::try_new(self)?;
+        Ok(())
+    }
+
+    fn try_inv(self) -> Result<Self,InvError> {
+        let inv=self.re.try_inv()?;
+        Ok(Self::new(inv.clone(), -self.eps*inv.clone()*inv))
+    }
+}
+
+impl<F:RealNumber> TryDiv for Dual<F> {
+    type Output=Self;
+    type Error=DivError;
+    fn is_divable_by(&self, rhs:&Self) -> Result<(),DivError> {
+        DivisionByZeroError::try_new(rhs)?;
+        Ok(())
+    }
+
+    fn try_div(self, rhs:Self) -> Result<Self,DivError> {
+        rhs.try_inv()
+           .map(|ri|self * ri)
+           .map_err(|e|e.into())
+    }
+}
+
+// the nonzero multiples of ε are not invertible. as for the division of floats by zero,
+// dividing by them does not panic but gives nan, try_div reports them as an error
+impl<F:RealNumber> Div<NonZero<Dual<F>>> for Dual<F> {
+    type Output=Self;
+    fn div(self, rhs:NonZero<Dual<F>>) -> Self {
+        self.try_div(rhs.into_inner())
+            .unwrap_or_else(|_|Self::new(F::from_f64(f64::NAN), F::from_f64(f64::NAN)))
+    }
+}
+
+impl<F> IntegralDomain for Dual<F> {}
+
+macro_rules! divi {
+    ($i:literal) => {
+        paste::paste!(
+        mod [<div $i>] {
+            use algebra_traits::operators::div_by_small_natural::[<Div $i>];
+            impl<F:[<Div $i>]> [<Div $i>] for super::Dual<F> {
+                fn [<div $i>](self) -> Self {
+                    super::Dual::new(self.re.[<div $i>](), self.eps.[<div $i>]())
+                }
+            }
+        });
+    }
+}
+impl_div2to10!(divi);
+
+// dual numbers are compared by their real parts and then by their ε parts, i.e. ε is a
+// positive infinitesimal. the order is consistent with the equality of both parts
+impl<F:RealNumber> PartialOrd for Dual<F> {
+    fn partial_cmp(&self, rhs:&Self) -> Option<Ordering> {
+        match self.re.partial_cmp(&rhs.re)? {
+            Ordering::Equal => self.eps.partial_cmp(&rhs.eps),
+            ordering        => Some(ordering),
+        }
+    }
+}
+
+impl<F:RealNumber> Max for Dual<F> {
+    fn max<'a>(&'a self, rhs:&'a Self) -> &'a Self {
+        if self > rhs {
+            self
+        } else {
+            rhs
+        }
+    }
+}
+
+impl<F:RealNumber> Norm for Dual<F> {
+    type NormT=Self;
+
+    fn norm(&self) -> Nonnegative<Self> {
+        self.clone().into_norm()
+    }
+
+    // negated if it is below zero in the order above, i.e. also for a vanishing real part and a
+    // negative ε part, such that |x| at the kink x=0 gives a nonnegative result
+    fn into_norm(self) -> Nonnegative<Self> {
+        let abs=if self.is_negative() { -self } else { self };
+        Nonnegative::try_new(abs).unwrap()
+    }
+}
+
+impl<F:RealNumber> NormSquared for Dual<F> {
+    type Norm2T=Self;
+
+    fn norm_squared(&self) -> Nonnegative<Self> {
+        self.clone().into_norm_squared()
+    }
+
+    fn into_norm_squared(self) -> Nonnegative<Self> {
+        Nonnegative::try_new(self.pow2()).unwrap()
+    }
+}
+
+impl<F:RealNumber> TryNormalize for Dual<F> {}
+
+impl<F:RealNumber> Distance for Dual<F> {
+    type DistT=Self;
+    fn into_distance(self, rhs:impl Into<Self>) -> Nonnegative<Self> {
+        let rhs:Self=rhs.into();
+        (rhs-self).into_norm()
+    }
+}
+
+impl<F:RealNumber> TryDistance for Dual<F> {
+    type TryDistT=Self;
+    type Error=Infallible;
+    fn try_into_distance(self, rhs:impl Into<Self>) -> Result<Nonnegative<Self>,Infallible> {
+        Ok(self.into_distance(rhs))
+    }
+}
+
+impl<F:RealNumber> Tolerance for Dual<F> {
+    const THRESHOLD:Self=Dual { re: F::THRESHOLD, eps: F::ZERO };
+}
+
+impl<F:RealNumber> Exp for Dual<F> {
+    type Output=Self;
+    fn exp(self) -> Self {
+        let e=self.re.exp();
+        Self::new(e.clone(), e*self.eps)
+    }
+}
+
+impl<F:RealNumber> TryLog for Dual<F> {
+    type Output=Self;
+    type Error=LogError;
+    fn is_logable(&self) -> Result<(),LogError> {
+        self.re.is_logable()
+    }
+
+    fn try_log(self) -> Result<Self,LogError> {
+        self.is_logable()?;
+        let eps=self.eps.try_div(self.re.clone()).unwrap();
+        self.re
+            .try_log()
+            .map(|l|Self::new(l, eps))
+    }
+}
+
+impl<F:RealNumber> TrySqrt for Dual<F> {
+    type Output=Nonnegative<Self>;
+    type Error=SqrtError;
+    fn is_sqrtable(&self) -> Result<(),SqrtError> {
+        self.clone()
+            .try_sqrt()
+            .map(|_|())
+    }
+
+    fn try_sqrt(self) -> Result<Nonnegative<Self>,SqrtError> {
+        let s=self.re.try_sqrt()?.into_signed();
+        // the derivative at zero is infinite unless the argument is stationary
+        let eps=if self.eps.is_zero() {
+            F::zero()
+        } else {
+            self.eps
+                .try_div(s.clone()+s.clone())
+                .map_err(|_|OverflowError)?
+        };
+        Ok(Nonnegative::try_new(Self::new(s, eps)).unwrap())
+    }
+}
+
+// d(a^c)=c*a^(c-1)*da+a^c*ln(a)*dc, the terms are only evaluated if the differential
+// does not vanish, such that e.g. powers of negative numbers with constant exponents work
+impl<F:RealNumber> TryPow for Dual<F> {
+    type Output=Self;
+    type Error=PowError;
+    fn is_powable_by(&self, rhs:&Self) -> Result<(),PowError> {
+        self.clone()
+            .try_pow(rhs.clone())
+            .map(|_|())
+    }
+
+    fn try_pow(self, rhs:Self) -> Result<Self,PowError> {
+        let [a,da]=self.into_re_eps();
+        let [c,dc]=rhs.into_re_eps();
+        let p=<F as TryPow<F>>::try_pow(a.clone(), c.clone())?;
+        let from_base=if da.is_zero() {
+            F::zero()
+        } else {
+            c.clone()*<F as TryPow<F>>::try_pow(a.clone(), c-F::one())?*da
+        };
+        let from_exponent=if dc.is_zero() {
+            F::zero()
+        } else {
+            let log=a.try_log()
+                     .map_err(|_|PowError::NonIntegralPowerOfNegativeNumberNotDefined)?;
+            p.clone()*log*dc
+        };
+        Ok(Self::new(p, from_base+from_exponent))
+    }
+}
+
+impl<F:RealNumber> TryPow<i16> for Dual<F> {
+    type Output=Self;
+    type Error=PowError;
+    fn is_powable_by(&self, rhs:&i16) -> Result<(),PowError> {
+        <Self as TryPow<Self>>::is_powable_by(self, &Self::from(*rhs))
+    }
+
+    fn try_pow(self, rhs:i16) -> Result<Self,PowError> {
+        <Self as TryPow<Self>>::try_pow(self, Self::from(rhs))
+    }
+}
+
+impl<F:RealNumber> TrigonometricFunctions for Dual<F> {
+    type Output=Self;
+
+    fn sin(self) -> Self {
+        Self::new(self.re.clone().sin(), self.eps*self.re.cos())
+    }
+
+    fn cos(self) -> Self {
+        Self::new(self.re.clone().cos(), -self.eps*self.re.sin())
+    }
+
+    fn tan(self) -> Result<Self,DivError> {
+        <Self as TryDiv>::try_div(self.clone().sin(), self.cos())
+    }
+}
+
+impl<F:RealNumber> TryATan2 for Dual<F> {
+    type Output=Self;
+    fn try_atan2(sin:Self, cos:Self) -> Option<Self> {
+        let [y,dy]=sin.into_re_eps();
+        let [x,dx]=cos.into_re_eps();
+        let r2=x.clone()*x.clone()+y.clone()*y.clone();
+        let eps=(x.clone()*dy-y.clone()*dx).try_div(r2).ok()?;
+        F::try_atan2(y, x).map(|a|Self::new(a, eps))
+    }
+}
+
+impl<F:RealNumber> Sinc for Dual<F> {
+    type Output=Self;
+    fn denominator(self) -> Self {
+        self
+    }
+}
+
+impl<F:RealNumber> TryIntoReal for Dual<F> {
+    type Output=Self;
+    fn try_into_real(self) -> Option<Self> {
+        Some(self)
+    }
+}
+
+impl<F:RealNumber> ScalarMul<Self> for Dual<F> {
+    fn scalar_mul(self, f:&Self) -> Self {
+        self * f.clone()
+    }
+}
+
+impl<F:RealNumber> TryScalarDiv<Self> for Dual<F> {
+    type Error=DivError;
+    fn is_scalar_divable_by(&self, f:&Self) -> Result<(),DivError> {
+        <Self as TryDiv>::is_divable_by(self, f)
+    }
+
+    fn try_scalar_div(self, f:&Self) -> Result<Self,DivError> {
+        <Self as TryDiv>::try_div(self, f.clone())
+    }
+}
+
+impl<F:RealNumber> Basis<Self> for Dual<F> {
+    fn basis() -> impl ExactSizeIterator<Item=Self> {
+        std::iter::once(Self::one())
+    }
+}
+
+impl<F:RealNumber> FiniteDimensionalVectorspace<Self,1> for Dual<F> {}
+
+impl<F:RealNumber> Scalarproduct for Dual<F> {
+    type ScProdT=Self;
+    fn into_scalar_product(self, rhs:Self) -> Self {
+        self * rhs
+    }
+}
+
+impl<F:RealNumber> TryScalarproduct for Dual<F> {
+    type TryScProdT=Self;
+    fn try_into_scalar_product(self, rhs:Self) -> Option<Self> {
+        Some(self * rhs)
+    }
+}
+
+macro_rules! impl_const {
+    ($name:ident, $const_name:ident) => {
+        impl<F:$name+ConstZero> $name for Dual<F> {
+            const $const_name:Self=Dual { re: F::$const_name, eps: F::ZERO };
+        }
+    };
+}
+impl_const!(ConstZero, ZERO);
+impl_const!(ConstOne, ONE);
+impl_const!(ConstNonZero, NONZERO);
+impl_const!(ConstPi, PI);
+impl_const!(ConstDeg2Rad, DEG2RAD);
+impl_const!(ConstRad2Deg, RAD2DEG);
+
+impl<F:RealNumber> From<i16> for Dual<F> {
+    fn from(value:i16) -> Self {
+        Self::constant(<F as From<i16>>::from(value))
+    }
+}
+
+impl<F:RealNumber> CastFromf64 for Dual<F> {
+    fn from_f64(value:f64) -> Self {
+        Self::constant(F::from_f64(value))
+    }
+}
+
+impl<F> IntoParameters<Dual<F>> for Dual<F> {
+    fn into_parameters(self) -> impl ExactSizeIterator<Item=Self> {
+        std::iter::once(self)
+    }
+}
+
+impl<F> AnyFromParameters<Dual<F>,LinearContainerConstructError> for Dual<F> {
+    fn any_take_away<I:Iterator<Item=Self>>(_:Option<&Self>, iter:& mut I) -> Result<Self,LinearContainerConstructError> {
+        iter.next()
+            .ok_or(LenTooSmallError::new(1,0).into())
+    }
+
+    container_traits::any_from_parameters_impl!(Dual<F>);
+}
+
+impl<F> TryFromIterator<Dual<F>,ContainerConstructError<usize>> for Dual<F> {
+    fn try_take_away<I:Iterator<Item=Self>>(iter:& mut I) -> Result<Self,ContainerConstructError<usize>> {
+        iter.next()
+            .ok_or(LenTooSmallError::new(1,0).into())
+    }
+}
+
+impl<F:RealNumber> Scalar for Dual<F> {
+    type RealType=Self;
+    fn basis_over_r() -> Vec<Self> {
+        vec![Self::one()]
+    }
+}
+
+impl<F:RealNumber> RealNumber for Dual<F> {}
+
+#[test]
+fn test_derivatives() {
+    let x=Dual::variable(0.7);
+    // d/dx sin(x)*exp(x)/sqrt(x)
+    let f=x.clone().sin()*x.clone().exp();
+    let f=f.try_div(x.clone().try_sqrt().unwrap().into_signed()).unwrap();
+    let df=(0.7_f64.cos()+0.7_f64.sin()-0.5*0.7_f64.sin()/0.7)*0.7_f64.exp()/0.7_f64.sqrt();
+    assert!((f.re() - 0.7_f64.sin()*0.7_f64.exp()/0.7_f64.sqrt()).abs() < 1e-14);
+    assert!((f.eps() - df).abs() < 1e-14);
+
+    // d/dx x^x=x^x*(ln(x)+1)
+    let f=x.clone().try_pow(x.clone()).unwrap();
+    assert!((f.eps() - 0.7_f64.powf(0.7)*(0.7_f64.ln()+1.0)).abs() < 1e-14);
+
+    // d/dx atan2(sin(x),cos(x))=1
+    let a=Dual::try_atan2(x.clone().sin(), x.clone().cos()).unwrap();
+    assert!((a.eps() - 1.0).abs() < 1e-14);
+
+    // constant exponent of a negative base
+    let f=Dual::variable(-2.0).try_pow(3_i16).unwrap();
+    assert_eq!(f, Dual::new(-8.0, 12.0));
+}
+
+#[test]
+fn test_order_consistent_with_equality() {
+    let a=Dual::new(1.0, 2.0);
+    let b=Dual::new(1.0, 3.0);
+    assert!(a != b);
+    assert_eq!(a.partial_cmp(&b), Some(Ordering::Less));
+    assert_eq!(a.partial_cmp(&a.clone()), Some(Ordering::Equal));
+    assert!(Dual::new(0.5, 10.0) < a);
+}
+
+#[test]
+fn test_division_by_multiple_of_eps() {
+    let x=Dual::new(1.0, 2.0);
+    let eps=Dual::new(0.0, 1.0);
+    assert!(x.clone().try_div(eps.clone()).is_err());
+    let q=x/NonZero::try_new(eps).unwrap();
+    assert!(q.re().is_nan() && q.eps().is_nan());
+}
+
+#[test]
+fn test_norm_at_zero_real_part() {
+    let x=Dual::new(0.0, -2.0);
+    assert_eq!(x.clone().into_norm().into_signed(), Dual::new(0.0, 2.0));
+    assert_eq!(x.into_distance(Dual::zero()).into_signed(), Dual::new(0.0, 2.0));
+    assert_eq!(Dual::new(-1.0, 3.0).into_norm().into_signed(), Dual::new(1.0, -3.0));
+}
@@ -1,6 +1,9 @@
 pub mod complex;
 pub use complex::{Complex, c64};
 
+pub mod dual;
+pub use dual::Dual;
+
 // pub mod finite;
 // pub use finite::Finite;
 
@@ -10,8 +10,12 @@ pub mod primitives;
 pub use primitives::{
     vector, Vector, Vector2, Vector3,
     UnitVector, UnitVector2, UnitVector3,
-    point, Point, Point2, Point3
+    point, Point, Point2, Point3,
+    UnitSphere
 };
 
 pub mod geodesic;
 pub use geodesic::Geodesic;
+
+pub mod pose_graph;
+pub use pose_graph::{PoseGraph, PoseGraphEdge, PoseGraphError};
@@ -0,0 +1,421 @@
+// pose graph optimization: the nodes are poses and the edges relative measurements z_ij of
+// x_i^-1*x_j with information matrices o_ij. the cost
+// sum e_ij^T*o_ij*e_ij with e_ij=log(z_ij^-1*x_i^-1*x_j)
+// in the tangent space LogSE3 is minimized by gauss-newton or levenberg-marquardt steps in the
+// local parameters of the free nodes. every edge jacobian has only the two 6x6 blocks of its nodes,
+// the normal equations are accumulated into a block sparse matrix and solved by conjugate gradients
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use num_traits::Zero;
+use container_traits::{FromFn, IntoParameters, Iter, TryFromLocalParameters};
+
+use algebra_traits::{LogError, TryLog};
+
+use algebra::VectorDyn;
+
+use matrix::MatrixDyn;
+use matrix_traits::MatrixView;
+use optimization::{jacobian_dvec, FiniteDifference, Method, OptimizationOptions, Whitening};
+use crate::trafos::SE3;
+use crate::trafos::special_euclidean::SETryFromParametersError;
+
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum PoseGraphError {
+    #[error("edge {0} refers to a node which does not exist")]
+    NodeIndex(usize),
+    #[error("fixed node {0} does not exist")]
+    FixedNodeIndex(usize),
+    #[error("the information matrix of edge {0} is not a positive definite 6x6 matrix")]
+    Information(usize),
+    #[error("logarithm of the error of an edge not possible {0}")]
+    Log(#[from] LogError),
+    #[error("problems updating a pose {0}")]
+    Update(#[from] SETryFromParametersError),
+    #[error("normal equations are singular, is every node connected to a fixed node?")]
+    Singular,
+    #[error("conjugate gradients did not converge, the normal equations are poorly conditioned")]
+    ConjugateGradients,
+    #[error("maximal number of iterations {0} reached")]
+    MaximalIteration(u8),
+}
+
+#[derive(Clone, Debug, derive_getters::Getters)]
+pub struct PoseGraphEdge {
+    from: usize,
+    to: usize,
+    measurement: SE3<f64>,
+    information: MatrixDyn<f64>,
+}
+
+impl PoseGraphEdge {
+    pub fn new(from:usize, to:usize, measurement:SE3<f64>, information:MatrixDyn<f64>) -> Self {
+        Self { from, to, measurement, information }
+    }
+}
+
+// without fixed nodes the first node is kept fixed, since the cost is invariant
+// under a common motion of all nodes
+#[derive(Clone, Debug, derive_getters::Getters)]
+pub struct PoseGraph {
+    nodes: Vec<SE3<f64>>,
+    edges: Vec<PoseGraphEdge>,
+    fixed: Vec<usize>,
+}
+
+fn retract(x:&SE3<f64>, delta:VectorDyn<f64>) -> Result<SE3<f64>, SETryFromParametersError> {
+    <SE3<f64> as TryFromLocalParameters<f64,SETryFromParametersError>>::try_from_iter(x.clone(), delta.into_iter())
+}
+
+// e_ij whitened by w with w^T*w=o_ij
+fn edge_residual(edge:&PoseGraphEdge, w:&MatrixDyn<f64>, xi:&SE3<f64>, xj:&SE3<f64>) -> Result<VectorDyn<f64>, LogError> {
+    let e:Vec<f64>=(edge.measurement.clone().inverse()*xi.clone().inverse()*xj.clone())
+        .try_log()?
+        .into_parameters()
+        .collect();
+    Ok(VectorDyn::from_iter((0..w.nrows()).map(|i|(0..e.len()).map(|k|w[(i,k)]*e[k]).sum())))
+}
+
+// derivative of the whitened error of an edge with respect to the local parameters of its first
+// or second node, the first failing retraction or logarithm is returned
+fn edge_jacobian(edge:&PoseGraphEdge, w:&MatrixDyn<f64>, xi:&SE3<f64>, xj:&SE3<f64>, first:bool, fd:FiniteDifference<f64>) -> Result<MatrixDyn<f64>, PoseGraphError> {
+    let error=RefCell::new(None);
+    let zero=||VectorDyn::from_iter([0.0; 6]);
+    let residual=|d:VectorDyn<f64>|{
+        let r=if first {
+            retract(xi, d).map_err(PoseGraphError::from)
+                          .and_then(|xi|Ok(edge_residual(edge, w, &xi, xj)?))
+        } else {
+            retract(xj, d).map_err(PoseGraphError::from)
+                          .and_then(|xj|Ok(edge_residual(edge, w, xi, &xj)?))
+        };
+        r.unwrap_or_else(|e|{
+            error.borrow_mut().get_or_insert(e);
+            zero()
+        })
+    };
+    let jac=jacobian_dvec(residual, zero(), fd);
+    match error.into_inner() {
+        Some(e) => Err(e),
+        None    => Ok(jac),
+    }
+}
+
+type Block=[[f64;6];6];
+
+// symmetric matrix of 6x6 blocks, every row stores its nonzero blocks by their column
+struct BlockSparse {
+    rows: Vec<BTreeMap<usize, Block>>,
+}
+
+fn dot(a:&[f64], b:&[f64]) -> f64 {
+    a.iter().zip(b.iter()).map(|(ai,bi)|ai*bi).sum()
+}
+
+impl BlockSparse {
+    fn new(nblocks:usize) -> Self {
+        Self { rows: (0..nblocks).map(|_|BTreeMap::new()).collect() }
+    }
+
+    fn block(&mut self, a:usize, b:usize) -> &mut Block {
+        self.rows[a].entry(b).or_insert([[0.0; 6]; 6])
+    }
+
+    fn diagonal(&self) -> Vec<f64> {
+        self.rows
+            .iter()
+            .enumerate()
+            .flat_map(|(a,row)|(0..6).map(move |p|row.get(&a).map_or(0.0, |block|block[p][p])))
+            .collect()
+    }
+
+    // (h+damping*diag(h))*v
+    fn damped_mul(&self, diagonal:&[f64], damping:f64, v:&[f64]) -> Vec<f64> {
+        let mut hv:Vec<f64>=v.iter().zip(diagonal.iter()).map(|(vi,di)|damping*di*vi).collect();
+        for (a, row) in self.rows.iter().enumerate() {
+            for (b, block) in row.iter() {
+                for p in 0..6 {
+                    hv[6*a+p]+=(0..6).map(|q|block[p][q]*v[6*b+q]).sum::<f64>();
+                }
+            }
+        }
+        hv
+    }
+
+    // conjugate gradients with the jacobi preconditioner for (h+damping*diag(h))*x=rhs
+    // until the residual is reduced by 1e-12. Singular if the matrix is not positive definite,
+    // ConjugateGradients if the residual is not reduced within 10*n iterations
+    fn try_solve(&self, rhs:&[f64], damping:f64) -> Result<Vec<f64>, PoseGraphError> {
+        let diagonal=self.diagonal();
+        if diagonal.iter().any(|d|d.is_nan() || *d <= 0.0) {
+            return Err(PoseGraphError::Singular);
+        }
+        let precondition=|r:&[f64]|r.iter().zip(diagonal.iter()).map(|(ri,di)|ri/(di*(1.0+damping))).collect::<Vec<f64>>();
+        let tol=1e-24*dot(rhs, rhs);
+        let mut x=vec![0.0; rhs.len()];
+        let mut r=rhs.to_vec();
+        let mut z=precondition(&r);
+        let mut p=z.clone();
+        let mut rz=dot(&r, &z);
+        for _ in 0..10*rhs.len() {
+            if dot(&r, &r) <= tol {
+                return Ok(x);
+            }
+            let hp=self.damped_mul(&diagonal, damping, &p);
+            let curvature=dot(&p, &hp);
+            if curvature.is_nan() || curvature <= 0.0 {
+                return Err(PoseGraphError::Singular);
+            }
+            let alpha=rz/curvature;
+            for ((xi, ri), (pi, hpi)) in x.iter_mut().zip(r.iter_mut()).zip(p.iter().zip(hp.iter())) {
+                *xi+=alpha*pi;
+                *ri-=alpha*hpi;
+            }
+            z=precondition(&r);
+            let rz_new=dot(&r, &z);
+            let beta=rz_new/rz;
+            rz=rz_new;
+            for (pi, zi) in p.iter_mut().zip(z.iter()) {
+                *pi=zi+beta*(*pi);
+            }
+        }
+        if dot(&r, &r) <= tol {
+            Ok(x)
+        } else {
+            Err(PoseGraphError::ConjugateGradients)
+        }
+    }
+}
+
+impl PoseGraph {
+    pub fn new(nodes:Vec<SE3<f64>>) -> Self {
+        Self { nodes, edges: Vec::new(), fixed: Vec::new() }
+    }
+
+    pub fn add_edge(&mut self, edge:PoseGraphEdge) -> Result<&mut Self, PoseGraphError> {
+        if edge.from >= self.nodes.len() || edge.to >= self.nodes.len() {
+            return Err(PoseGraphError::NodeIndex(self.edges.len()));
+        }
+        self.edges.push(edge);
+        Ok(self)
+    }
+
+    pub fn fix(&mut self, node:usize) -> Result<&mut Self, PoseGraphError> {
+        if node >= self.nodes.len() {
+            return Err(PoseGraphError::FixedNodeIndex(node));
+        }
+        self.fixed.push(node);
+        Ok(self)
+    }
+
+    pub fn into_nodes(self) -> Vec<SE3<f64>> {
+        self.nodes
+    }
+
+    // the column block of every node, None for fixed nodes
+    fn blocks(&self) -> (Vec<Option<usize>>, usize) {
+        let fixed=|k:usize|if self.fixed.is_empty() { k == 0 } else { self.fixed.contains(&k) };
+        let mut nfree=0;
+        let blocks=(0..self.nodes.len()).map(|k|
+            if fixed(k) {
+                None
+            } else {
+                nfree+=1;
+                Some(nfree-1)
+            }).collect();
+        (blocks, nfree)
+    }
+
+    fn cost(&self, nodes:&[SE3<f64>], whitenings:&[MatrixDyn<f64>]) -> Result<f64, PoseGraphError> {
+        let mut cost=0.0;
+        for (edge, w) in self.edges.iter().zip(whitenings.iter()) {
+            let r=edge_residual(edge, w, &nodes[edge.from], &nodes[edge.to])?;
+            cost+=r.iter().map(|ri|ri*ri).sum::<f64>();
+        }
+        Ok(cost)
+    }
+
+    // the gauss-newton matrix j^T*j and the right hand side -j^T*r
+    fn normal_equations(&self,
+                        nodes:&[SE3<f64>],
+                        whitenings:&[MatrixDyn<f64>],
+                        blocks:&[Option<usize>],
+                        nfree:usize,
+                        fd:&FiniteDifference<f64>) -> Result<(BlockSparse, Vec<f64>), PoseGraphError> {
+        let mut normal=BlockSparse::new(nfree);
+        let mut rhs=vec![0.0; 6*nfree];
+        for (edge, w) in self.edges.iter().zip(whitenings.iter()) {
+            let (xi, xj)=(&nodes[edge.from], &nodes[edge.to]);
+            let r=edge_residual(edge, w, xi, xj)?;
+            let mut jacs=Vec::with_capacity(2);
+            for (node, first) in [(edge.from, true), (edge.to, false)] {
+                if let Some(b) = blocks[node] {
+                    jacs.push((b, edge_jacobian(edge, w, xi, xj, first, fd.clone())?));
+                }
+            }
+            for (ba, ja) in jacs.iter() {
+                for p in 0..6 {
+                    rhs[6*ba+p]-=(0..6).map(|k|ja[(k,p)]*r[k]).sum::<f64>();
+                }
+                for (bb, jb) in jacs.iter() {
+                    let block=normal.block(*ba, *bb);
+                    for p in 0..6 {
+                        for q in 0..6 {
+                            block[p][q]+=(0..6).map(|k|ja[(k,p)]*jb[(k,q)]).sum::<f64>();
+                        }
+                    }
+                }
+            }
+        }
+        Ok((normal, rhs))
+    }
+
+    // returns the optimized poses, the damping of levenberg-marquardt is relative to the diagonal
+    // of the normal equations and kept within [min_damping, max_damping]. as in the solvers of
+    // optimization every rejected step counts as an iteration, so does a failure of the
+    // conjugate gradients, after which the damping is increased
+    pub fn optimize(&self, opts:Option<OptimizationOptions<f64>>) -> Result<Vec<SE3<f64>>, PoseGraphError> {
+        let opts=opts.unwrap_or_default();
+        let mut whitenings=Vec::with_capacity(self.edges.len());
+        for (e, edge) in self.edges.iter().enumerate() {
+            match Whitening::try_from_information(edge.information.clone()) {
+                Some(w) if w.matrix().nrows() == 6 => whitenings.push(w.matrix().clone()),
+                _                                  => { return Err(PoseGraphError::Information(e)); },
+            }
+        }
+        let (blocks, nfree)=self.blocks();
+        let (initial, increase, decrease, min_damping, max_damping)=match opts.method() {
+            Method::LevenbergMarquardt(lm) => (*lm.initial_damping(), *lm.increase(), *lm.decrease(), *lm.min_damping(), *lm.max_damping()),
+            Method::GaussNewton            => (0.0, 1.0, 1.0, 0.0, 0.0),
+        };
+        let clamp=|damping:f64|damping.max(min_damping).min(max_damping);
+        let mut damping=clamp(initial);
+        let mut nodes=self.nodes.clone();
+        if nfree == 0 {
+            return Ok(nodes);
+        }
+        let mut cost=self.cost(&nodes, &whitenings)?;
+        // the normal equations are kept after a rejected step
+        let mut normal=None;
+        let mut iter:u8=0;
+        while &iter < opts.max_iter() {
+            let (h, rhs)=match normal.take() {
+                Some(normal) => normal,
+                None         => self.normal_equations(&nodes, &whitenings, &blocks, nfree, opts.fd())?,
+            };
+            let delta=match h.try_solve(&rhs, damping) {
+                Ok(delta) => delta,
+                // a larger damping improves the condition of the normal equations
+                Err(PoseGraphError::ConjugateGradients) if clamp(damping*increase) > damping => {
+                    damping=clamp(damping*increase);
+                    normal=Some((h, rhs));
+                    iter+=1;
+                    continue;
+                },
+                Err(e) => { return Err(e); },
+            };
+            if &dot(&delta, &delta).sqrt() < opts.target_cost() {
+                return Ok(nodes);
+            }
+            let mut candidate=nodes.clone();
+            for (x, b) in candidate.iter_mut().zip(blocks.iter()) {
+                if let Some(b) = b {
+                    *x=retract(x, VectorDyn::from_iter((0..6).map(|p|delta[6*b+p])))?;
+                }
+            }
+            let candidate_cost=self.cost(&candidate, &whitenings)?;
+            if damping.is_zero() || candidate_cost < cost {
+                nodes=candidate;
+                cost=candidate_cost;
+                damping=clamp(damping*decrease);
+            } else {
+                damping=clamp(damping*increase);
+                normal=Some((h, rhs));
+            }
+            iter+=1;
+        }
+        Err(PoseGraphError::MaximalIteration(iter))
+    }
+}
+
+#[test]
+fn test_pose_graph_loop() {
+    use container_traits::for_static::TryFromParameters;
+    use optimization::{LevenbergMarquardtOptions, OptimizationOptionsBuilder};
+    let pose=|vs:[f64;6]|<SE3<f64> as TryFromParameters<f64,SETryFromParametersError>>::try_from_iter(vs).unwrap();
+    // a square with a quarter turn at every corner
+    let truth:Vec<SE3<f64>>=[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]
+        .iter()
+        .enumerate()
+        .map(|(k,t)|pose([0.0, 0.0, 0.5*std::f64::consts::PI*(k as f64), t[0], t[1], 0.0]))
+        .collect();
+    let perturbations=[[0.0; 6],
+                       [0.05, -0.02, 0.1, 0.1, -0.05, 0.02],
+                       [-0.03, 0.04, -0.08, -0.1, 0.15, -0.05],
+                       [0.02, 0.03, 0.05, 0.05, 0.1, 0.1]];
+    let guess=truth.iter().zip(perturbations).map(|(x,p)|x.clone()*pose(p)).collect();
+    let identity=||MatrixDyn::from_fn((6,6),|(i,j)|if i == j { 1.0 } else { 0.0 });
+    let mut graph=PoseGraph::new(guess);
+    // the loop 0-1-2-3-0 and a diagonal
+    for (i, j) in [(0, 1), (1, 2), (2, 3), (3, 0), (0, 2)] {
+        graph.add_edge(PoseGraphEdge::new(i, j, truth[i].clone().inverse()*truth[j].clone(), identity())).unwrap();
+    }
+    graph.fix(0).unwrap();
+    let opts=OptimizationOptionsBuilder::default()
+        .method(Method::LevenbergMarquardt(LevenbergMarquardtOptions::default()))
+        .max_iter(50)
+        .build()
+        .unwrap();
+    let nodes=graph.optimize(Some(opts)).unwrap();
+    for (x, t) in nodes.iter().zip(truth.iter()) {
+        let diff:Vec<f64>=(t.clone().inverse()*x.clone()).into_parameters().collect();
+        assert!(diff.iter().all(|d|d.abs() < 1e-6));
+    }
+}
+
+#[test]
+fn test_pose_graph_node_indices() {
+    use container_traits::for_static::TryFromParameters;
+    let identity=<SE3<f64> as TryFromParameters<f64,SETryFromParametersError>>::try_from_iter([0.0; 6]).unwrap();
+    let information=MatrixDyn::from_fn((6,6),|(i,j)|if i == j { 1.0 } else { 0.0 });
+    let mut graph=PoseGraph::new(vec![identity.clone(), identity.clone()]);
+    graph.add_edge(PoseGraphEdge::new(0, 1, identity.clone(), information.clone())).unwrap();
+    assert!(matches!(graph.add_edge(PoseGraphEdge::new(1, 2, identity, information)), Err(PoseGraphError::NodeIndex(1))));
+    assert!(matches!(graph.fix(2), Err(PoseGraphError::FixedNodeIndex(2))));
+    assert_eq!(graph.edges().len(), 1);
+    assert!(graph.fixed().is_empty());
+}
+
+#[test]
+fn test_conjugate_gradients_on_poorly_conditioned_chain() {
+    // a chain of 10 free nodes hanging from a fixed one, the information of the edges alternates
+    // between 1e-8 and 1e8
+    let n=10;
+    let mut h=BlockSparse::new(n);
+    for k in 0..n {
+        let w=if k % 2 == 0 { 1e-8 } else { 1e8 };
+        let mut add=|a:usize, b:usize, s:f64|{
+            let block=h.block(a, b);
+            for p in 0..6 {
+                block[p][p]+=s*w;
+            }
+        };
+        add(k, k, 1.0);
+        if k > 0 {
+            add(k-1, k-1, 1.0);
+            add(k-1, k, -1.0);
+            add(k, k-1, -1.0);
+        }
+    }
+    let rhs:Vec<f64>=(0..6*n).map(|i|1.0+(i % 7) as f64).collect();
+    // the iteration limit is reached without damping
+    assert!(matches!(h.try_solve(&rhs, 0.0), Err(PoseGraphError::ConjugateGradients)));
+    let diagonal=h.diagonal();
+    let damping=1e-3;
+    let x=h.try_solve(&rhs, damping).unwrap();
+    let r:Vec<f64>=h.damped_mul(&diagonal, damping, &x)
+                    .iter()
+                    .zip(rhs.iter())
+                    .map(|(hxi,ri)|hxi-ri)
+                    .collect();
+    assert!(dot(&r, &r) <= 1e-20*dot(&rhs, &rhs));
+}
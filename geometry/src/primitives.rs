@@ -26,4 +26,7 @@ pub mod plane;
 pub use plane::Plane;
 
 pub mod triangle;
-pub use triangle::Triangle;
\ No newline at end of file
+pub use triangle::Triangle;
+
+pub mod sphere;
+pub use sphere::UnitSphere;
\ No newline at end of file
@@ -0,0 +1,165 @@
+// the unit sphere around the origin as a submanifold of the points in three dimensions,
+// e.g. for the minimization of functions of directions by the riemannian newton method
+use num_traits::{One, Zero};
+use algebra_traits::{RealNumber, TryDiv, TrySqrt};
+use container_traits::for_static::NumberOfDegreesOfFreedom;
+use geometry_traits::Submanifold;
+
+use super::{Point3, Vector3};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnitSphere<F>(Point3<F>);
+
+fn coordinates<F:Clone>(p:&Point3<F>) -> [F;3] {
+    [p.x().clone(), p.y().clone(), p.z().clone()]
+}
+
+fn components<F:Clone>(v:&Vector3<F>) -> [F;3] {
+    [v.x().clone(), v.y().clone(), v.z().clone()]
+}
+
+fn dot<F:RealNumber>(a:&[F;3], b:&[F;3]) -> F {
+    a.iter()
+     .zip(b.iter())
+     .fold(F::zero(),|acc,(ai,bi)|acc+ai.clone()*bi.clone())
+}
+
+// a/|a|, the zero vector has no direction
+fn normalize<F:RealNumber>(a:[F;3]) -> [F;3] {
+    let n=dot(&a, &a).try_sqrt()
+                     .unwrap()
+                     .into_signed();
+    a.map(|ai|ai.try_div(n.clone()).unwrap())
+}
+
+impl<F:RealNumber> UnitSphere<F> {
+    pub fn point(&self) -> &Point3<F> {
+        &self.0
+    }
+
+    // orthonormal basis of the tangent space, the first vector is the projection of
+    // the axis along which the point has the smallest component
+    fn tangent_basis(&self) -> [[F;3];2] {
+        let x=coordinates(&self.0);
+        let k=(0..3).fold(0,|k,i|if x[i].clone()*x[i].clone() < x[k].clone()*x[k].clone() { i } else { k });
+        let t1=normalize(std::array::from_fn(|i|(if i == k { F::one() } else { F::zero() })-x[k].clone()*x[i].clone()));
+        let t2=[x[1].clone()*t1[2].clone()-x[2].clone()*t1[1].clone(),
+                x[2].clone()*t1[0].clone()-x[0].clone()*t1[2].clone(),
+                x[0].clone()*t1[1].clone()-x[1].clone()*t1[0].clone()];
+        [t1, t2]
+    }
+}
+
+impl<F:RealNumber> NumberOfDegreesOfFreedom<F> for UnitSphere<F> {
+    const NDOFS:usize=2;
+}
+
+impl<F:RealNumber> Submanifold<F> for UnitSphere<F> {
+    type AmbientSpace=Point3<F>;
+    type V=Vector3<F>;
+
+    fn embedding(self) -> Point3<F> {
+        self.0
+    }
+
+    // panics for the origin
+    fn project(e:Point3<F>) -> Self {
+        let [x, y, z]=normalize(coordinates(&e));
+        UnitSphere(Point3::new(x, y, z))
+    }
+
+    fn tangent_space(self, vs:Vec<F>) -> Point3<F> {
+        let [t1, t2]=self.tangent_basis();
+        let x=coordinates(&self.0);
+        let [a, b, c]=std::array::from_fn(|k|x[k].clone()+vs[0].clone()*t1[k].clone()+vs[1].clone()*t2[k].clone());
+        Point3::new(a, b, c)
+    }
+
+    // second derivative of e -> e/|e| at |e|=1
+    fn d2project(e:Point3<F>, v:Vector3<F>) -> Vector3<F> {
+        let e=coordinates(&e);
+        let v=components(&v);
+        let ev=dot(&e, &v);
+        let vv=dot(&v, &v);
+        let two=F::one()+F::one();
+        let three=two.clone()+F::one();
+        let [a, b, c]=std::array::from_fn(|k|-two.clone()*ev.clone()*v[k].clone()
+                                               -vv.clone()*e[k].clone()
+                                               +three.clone()*ev.clone()*ev.clone()*e[k].clone());
+        Vector3::new(a, b, c)
+    }
+}
+
+// A has the eigenvalues 2-sqrt(2), 2, 2+sqrt(2), the first one with eigenvector (1,-sqrt(2),1)/2
+#[cfg(test)]
+fn rayleigh_matrix_product(x:&[f64;3]) -> [f64;3] {
+    [2.0*x[0]+x[1], x[0]+2.0*x[1]+x[2], x[1]+2.0*x[2]]
+}
+
+#[cfg(test)]
+fn rayleigh_quotient(p:Point3<f64>) -> f64 {
+    let x=coordinates(&p);
+    dot(&x, &rayleigh_matrix_product(&x))/dot(&x, &x)
+}
+
+#[cfg(test)]
+fn check_minimal_eigenvector(s:UnitSphere<f64>) {
+    let x=coordinates(s.point());
+    let expected=[0.5, -0.5*2.0_f64.sqrt(), 0.5];
+    let sign=if x[0] > 0.0 { 1.0 } else { -1.0 };
+    for k in 0..3 {
+        assert!((sign*x[k] - expected[k]).abs() < 1e-6);
+    }
+    assert!((rayleigh_quotient(s.embedding()) - (2.0 - 2.0_f64.sqrt())).abs() < 1e-10);
+}
+
+#[cfg(test)]
+fn rayleigh_gradient(p:Point3<f64>) -> Vector3<f64> {
+    let x=coordinates(&p);
+    let n2=dot(&x, &x);
+    let q=rayleigh_quotient(p);
+    let ax=rayleigh_matrix_product(&x);
+    let [a, b, c]=std::array::from_fn(|k|2.0*(ax[k]-q*x[k])/n2);
+    Vector3::new(a, b, c)
+}
+
+#[test]
+fn test_rayleigh_quotient_with_gradient() {
+    let opts=optimization::OptimizationOptionsBuilder::default()
+        .max_iter(50)
+        .build()
+        .unwrap();
+    let x0=UnitSphere::project(Point3::new(1.0, -1.0, 0.5));
+    let xsol=optimization::riemannian_newton_with_gradient(rayleigh_quotient, rayleigh_gradient, x0, Some(opts)).unwrap();
+    check_minimal_eigenvector(xsol);
+}
+
+#[test]
+fn test_rayleigh_quotient_numerical() {
+    use algebra_traits::NonZero;
+    use optimization::{FiniteDifference, FiniteDifferenceMethod};
+    // the step can not become smaller than the error of the numerical gradient
+    let opts=optimization::OptimizationOptionsBuilder::default()
+        .max_iter(50)
+        .target_cost(1e-7)
+        .build()
+        .unwrap();
+    let x0=UnitSphere::project(Point3::new(0.3, 0.2, 1.0));
+    // the errors of the numerical gradient of the options are divided by the step of the hessian
+    let hessian_fd=FiniteDifference::new(NonZero::try_new(1e-4).unwrap(), FiniteDifferenceMethod::Centered);
+    let xsol=optimization::riemannian_newton(rayleigh_quotient, x0, hessian_fd, Some(opts)).unwrap();
+    check_minimal_eigenvector(xsol);
+}
+
+#[test]
+fn test_rayleigh_quotient_with_wrong_gradient() {
+    // near the maximal eigenvector (1,sqrt(2),1)/2 the negated gradient yields a newton step
+    // towards the maximum, no step along it decreases the cost
+    let gradient=|p:Point3<f64>|{
+        let [a, b, c]=components(&rayleigh_gradient(p));
+        Vector3::new(-a, -b, -c)
+    };
+    let x0=UnitSphere::project(Point3::new(0.5, 0.6, 0.5));
+    let res=optimization::riemannian_newton_with_gradient(rayleigh_quotient, gradient, x0, None);
+    assert!(matches!(res, Err(optimization::OptimizationError::NoDecrease(_))));
+}
@@ -382,3 +382,26 @@ fn test_parameters_se3() {
     let jac = jacobian(&f, &vs, FiniteDifference::default());
     assert!((jac.try_sub(MatrixDyn::<f64>::identity(6)).unwrap()).max_norm_of_entries() < 1e-6);
 }
+
+#[test]
+fn test_pose_on_manifold() {
+    use optimization::ProblemBuilder;
+    let pts = [Point3::new(1.0, 0.0, 0.0), Point3::new(0.0, 2.0, 0.0),
+               Point3::new(0.0, 0.0, 1.0), Point3::new(1.0, 1.0, 1.0)];
+    let transform = |se3: SE3<f64>| pts.iter()
+                                      .flat_map(|pt|{
+                                          let q=se3.clone()*pt.clone();
+                                          [q[0], q[1], q[2]]
+                                      })
+                                      .collect::<Vec<f64>>();
+    let vs = [0.1, -0.2, 0.3, 1.0, 2.0, -0.5];
+    let pose = SE3::<f64>::try_from_parameters(vs.iter()).unwrap();
+    let problem = ProblemBuilder::<f64,_,_,_>::new(&transform, SE3::<f64>::identity())
+        .target(transform(pose))
+        .set_weights_to_one()
+        .build().unwrap();
+    let sol = problem.solve_on_manifold::<SETryFromParametersError>().unwrap();
+    for (p, v) in sol.parameters().iter().zip(vs) {
+        assert!((p - v).abs() < 1e-8);
+    }
+}
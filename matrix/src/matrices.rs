@@ -1,6 +1,6 @@
 
 pub mod matrix_dyn;
-pub use matrix_dyn::{MatrixDyn,SquareMatrixDyn};
+pub use matrix_dyn::{MatrixDyn,SquareMatrixDyn,SymmetricMatrixDyn};
 
 pub mod matrix_generic;
 pub use matrix_generic::MatrixGeneric;
@@ -1,5 +1,6 @@
 use algebra::VectorDyn;
-use container_traits::{ChangeT, Empty, IndexOutOfBoundsError, IntoIter, IterMut, Len, LenNotEqualToRequiredLenError, OneElement, Pop, Push, TryInsert, TryRemove, Zeros};
+use algebra_traits::RealNumber;
+use container_traits::{ChangeT, Empty, IndexOutOfBoundsError, IntoInner, IntoIter, IterMut, Len, LenNotEqualToRequiredLenError, OneElement, Pop, Push, TryInsert, TryRemove, Zeros};
 
 use either::Either;
 use matrix_traits::*;
@@ -287,6 +288,15 @@ impl<F   : 'static,
 
 pub type SquareMatrixDyn<F>=Square<MatrixDyn<F>>;
 
+pub type SymmetricMatrixDyn<F>=matrix_wrappers::Symmetric<SquareMatrixDyn<F>>;
+
+impl<F:RealNumber> From<SymmetricMatrixDyn<F>> for MatrixDyn<F> {
+    fn from(m:SymmetricMatrixDyn<F>) -> Self {
+        m.into_inner()
+         .into_base_matrix()
+    }
+}
+
 matrix_traits::impl_op_diag_dyn! (MatrixRowDyn,MatrixDyn);
 
 
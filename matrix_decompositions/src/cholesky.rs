@@ -0,0 +1,56 @@
+// cholesky.rs
+
+// factorization m=l*l^H of a hermitian positive definite matrix m, l is lower triangular
+// with positive real diagonal. only the lower triangle of m is read
+use algebra_traits::{CastFromf64, Conjugate, RealNumber, Scalar, TryDiv, TryIntoReal, TrySqrt};
+use container_traits::{Get, TryFromFn};
+use matrix_traits::*;
+
+pub trait Cholesky : Sized {
+    // None if the matrix is not square or not positive definite
+    fn try_cholesky(&self) -> Option<Self>;
+}
+
+impl<F : Clone+Scalar,
+     M : MatrixTryConstruct<T=F>> Cholesky for M {
+    fn try_cholesky(&self) -> Option<Self> {
+        let (n, ncols)=self.matrix_dimensions();
+        if ncols != n {
+            return None;
+        }
+        let m=|i:usize, j:usize|self.get((i,j)).ok().unwrap().clone();
+        let mut l:Vec<Vec<F>>=(0..n).map(|_|(0..n).map(|_|F::zero()).collect()).collect();
+        for j in 0..n {
+            let d=(0..j).fold(m(j,j),|acc,k|acc-l[j][k].clone()*l[j][k].conjugate());
+            // the imaginary part vanishes up to rounding
+            let d=(d.clone()+d.conjugate()).try_into_real()?*F::RealType::from_f64(0.5);
+            if !d.is_positive() {
+                return None;
+            }
+            let ljj=F::from(d.try_sqrt().ok()?.into_signed());
+            for i in j+1..n {
+                let s=(0..j).fold(m(i,j),|acc,k|acc-l[i][k].clone()*l[j][k].conjugate());
+                l[i][j]=s.try_div(ljj.clone()).ok()?;
+            }
+            l[j][j]=ljj;
+        }
+        Self::try_from_fn((n,n),|(i,j)|l[i][j].clone()).ok()
+    }
+}
+
+#[cfg(test)]
+mod test_with_nalgebra {
+    use nalgebra::SMatrix;
+
+    use super::Cholesky;
+
+    #[test]
+    fn test_cholesky_smatrix() {
+        let m:SMatrix<f64,3,3>=nalgebra::matrix![4.0, 2.0, 0.0; 2.0, 5.0, 2.0; 0.0, 2.0, 5.0];
+        let l=m.try_cholesky().unwrap();
+        assert!(l[(0,1)] == 0.0 && l[(0,2)] == 0.0 && l[(1,2)] == 0.0);
+        assert!((l*l.transpose() - m).abs().max() < 1e-12);
+        let indefinite:SMatrix<f64,2,2>=nalgebra::matrix![1.0, 2.0; 2.0, 1.0];
+        assert!(indefinite.try_cholesky().is_none());
+    }
+}
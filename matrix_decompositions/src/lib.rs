@@ -2,4 +2,7 @@
 // pub use eig::*;
 
 pub mod qr;
-pub use qr::*;
\ No newline at end of file
+pub use qr::*;
+
+pub mod cholesky;
+pub use cholesky::Cholesky;
@@ -0,0 +1,76 @@
+use algebra_traits::{Interval, RealNumber, Scalar, TryIntoReal};
+use algebra::VectorDyn;
+use container_traits::{IntoParameters, Iter, Len};
+
+// lower and upper bound for every parameter of the unknown
+#[derive(Clone, Debug)]
+pub struct Bounds<R> {
+    lower: VectorDyn<R>,
+    upper: VectorDyn<R>,
+}
+
+impl<R:RealNumber> Bounds<R> {
+    pub fn from_intervals(intervals:impl IntoIterator<Item=Interval<R>>) -> Self {
+        let (lower,upper):(Vec<R>,Vec<R>)=
+            intervals.into_iter()
+                     .map(|iv|(iv.lb().clone(),iv.ub().clone()))
+                     .unzip();
+        Self { lower: lower.into(), upper: upper.into() }
+    }
+
+    pub fn from_pair<F:Scalar<RealType=R>, X:IntoParameters<F>>(lower:X, upper:X) -> Self {
+        let real=|x:X|VectorDyn::from_iter(x.into_parameters().map(|f|f.try_into_real().unwrap()));
+        Self { lower: real(lower), upper: real(upper) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lower.len()
+    }
+
+    // index of the first parameter whose lower bound is larger than its upper bound
+    pub fn try_check(&self) -> Result<(), usize> {
+        match self.lower.iter().zip(self.upper.iter()).position(|(l,u)|l > u) {
+            Some(i) => Err(i),
+            None => Ok(()),
+        }
+    }
+
+    pub fn lower(&self, i:usize) -> &R {
+        &self.lower[i]
+    }
+
+    pub fn upper(&self, i:usize) -> &R {
+        &self.upper[i]
+    }
+
+    pub fn contains<F:Scalar<RealType=R>>(&self, x:&VectorDyn<F>) -> bool {
+        x.iter()
+         .enumerate()
+         .all(|(i,xi)|{
+            let xi=xi.clone().try_into_real().unwrap();
+            self.lower(i) <= &xi && &xi <= self.upper(i)
+         })
+    }
+
+    pub fn project<F:Scalar<RealType=R>>(&self, x:VectorDyn<F>) -> VectorDyn<F> {
+        VectorDyn::from_iter(
+            x.into_iter()
+             .enumerate()
+             .map(|(i,xi)|{
+                let xr=xi.clone().try_into_real().unwrap();
+                if      &xr < self.lower(i) { F::from(self.lower(i).clone()) }
+                else if &xr > self.upper(i) { F::from(self.upper(i).clone()) }
+                else { xi }
+             }))
+    }
+}
+
+#[test]
+fn test_bounds() {
+    let bounds=Bounds::from_pair([0.0, -1.0], [1.0, 1.0]);
+    assert!(bounds.try_check().is_ok());
+    assert!(bounds.contains(&VectorDyn::from(vec![0.5, 1.0])));
+    assert!(!bounds.contains(&VectorDyn::from(vec![1.5, 0.0])));
+    assert_eq!(bounds.project(VectorDyn::from(vec![1.5, -2.0])), VectorDyn::from(vec![1.0, -1.0]));
+    assert_eq!(Bounds::from_pair([0.0, 2.0], [1.0, 1.0]).try_check(), Err(1));
+}
@@ -0,0 +1,124 @@
+// fit of a model g(p, t) to a table of data points (t_i, y_i) with standard deviations sigma_i,
+// the residuals of all points are concatenated into a weighted least squares problem
+use num_traits::{One, Zero};
+use container_traits::{AnyParameters, IntoParameters, LenNotEqualToRequiredLenError, LenTooSmallError, LinearContainerConstructError as LCCE};
+
+use algebra_traits::{CastFromf64, RealNumber, TryDiv};
+
+use algebra::VectorDyn;
+
+use crate::ProblemBuilder;
+use super::{OptimizationError, OptimizationOptions};
+
+// the standard errors are scaled by the reduced chi-square, both are None if there are not more
+// data points than parameters or the jacobian does not have full rank.
+// r_squared is the unweighted coefficient of determination 1-SS_res/SS_tot,
+// None if all data values are equal
+#[derive(Clone, Debug, derive_getters::Getters)]
+pub struct CurveFit<R,P> {
+    parameters: P,
+    standard_errors: Option<Vec<R>>,
+    reduced_chi_square: Option<R>,
+    r_squared: Option<R>,
+}
+
+impl<R,P> CurveFit<R,P> {
+    pub fn into_parameters(self) -> P {
+        self.parameters
+    }
+}
+
+fn sum_of_squares<R:RealNumber>(v:impl Iterator<Item=R>) -> R {
+    v.fold(R::zero(),|acc,vi|acc+vi.clone()*vi)
+}
+
+pub fn curve_fit<
+    R : RealNumber,
+    P : Clone+AnyParameters<R,LCCE>,
+    T : Clone>(
+        model  : impl Fn(P, T) -> R,
+        p0     : P,
+        ts     : &[T],
+        ys     : &[R],
+        sigmas : &[R],
+        opts   : Option<OptimizationOptions<R>>) -> Result<CurveFit<R,P>, OptimizationError<R,P>> {
+    let n=ts.len();
+    if n == 0 {
+        return Err(OptimizationError::ContainerConstruct(LenTooSmallError::new(1, 0).into()));
+    }
+    for len in [ys.len(), sigmas.len()] {
+        if len != n {
+            return Err(OptimizationError::ContainerConstruct(LenNotEqualToRequiredLenError::new(n, len).into()));
+        }
+    }
+    let weights=sigmas.iter()
+                      .enumerate()
+                      .map(|(i,s)|R::one().try_div(s.clone())
+                                          .ok()
+                                          .filter(|_|s.is_positive())
+                                          .ok_or(OptimizationError::NonpositiveStandardDeviation(i)))
+                      .collect::<Result<Vec<R>,_>>()?;
+    let f=|p:P|ts.iter().map(|t|model(p.clone(), t.clone())).collect::<Vec<R>>();
+    let problem=ProblemBuilder::<R,_,_,_>::new(&f, p0)
+        .target(ys.to_vec())
+        .weights(VectorDyn::from(weights.clone()))
+        .options(opts.unwrap_or_default())
+        .build()?;
+    let parameters=problem.solve()?;
+    let fitted=f(parameters.clone());
+    let npar=parameters.clone().into_parameters().count();
+    let (standard_errors, reduced_chi_square)=match problem.uncertainties(parameters.clone())? {
+        Some(unc) if n > npar => {
+            let chi_square=sum_of_squares(fitted.iter()
+                                                .zip(ys.iter().zip(weights.iter()))
+                                                .map(|(fi,(yi,wi))|(yi.clone()-fi.clone())*wi.clone()));
+            let standard_errors=unc.standard_deviations()
+                                   .iter()
+                                   .map(|sd|sd.clone().into_signed())
+                                   .collect();
+            (Some(standard_errors), chi_square.try_div(R::from_f64((n-npar) as f64)).ok())
+        },
+        _ => (None, None),
+    };
+    let mean=ys.iter().fold(R::zero(),|acc,yi|acc+yi.clone()).try_div(R::from_f64(n as f64)).ok();
+    let ss_tot=mean.map(|mean|sum_of_squares(ys.iter().map(|yi|yi.clone()-mean.clone())));
+    let ss_res=sum_of_squares(ys.iter().zip(fitted.iter()).map(|(yi,fi)|yi.clone()-fi.clone()));
+    let r_squared=ss_tot.and_then(|ss_tot|ss_res.try_div(ss_tot).ok())
+                        .map(|ratio|R::one()-ratio);
+    Ok(CurveFit{parameters, standard_errors, reduced_chi_square, r_squared})
+}
+
+#[test]
+fn test_curve_fit_line() {
+    // the residuals of the exact line 1+2t are orthogonal to the columns of the jacobian
+    let ts=[0.0, 1.0, 2.0, 3.0, 4.0];
+    let es=[0.1, -0.2, 0.0, 0.2, -0.1];
+    let ys:Vec<f64>=ts.iter().zip(es.iter()).map(|(t,e)|1.0+2.0*t+e).collect();
+    let fit=curve_fit(|p:[f64;2], t:f64|p[0]+p[1]*t, [0.0, 0.0], &ts, &ys, &[0.5; 5], None).unwrap();
+    assert!((fit.parameters()[0] - 1.0).abs() < 1e-8);
+    assert!((fit.parameters()[1] - 2.0).abs() < 1e-8);
+    // chi-square 0.1/0.5^2 with three degrees of freedom
+    assert!((fit.reduced_chi_square().unwrap() - 0.4/3.0).abs() < 1e-8);
+    // the standard errors do not depend on the scale of the sigmas
+    let se=fit.standard_errors().clone().unwrap();
+    assert!((se[0] - 0.02_f64.sqrt()).abs() < 1e-6);
+    assert!((se[1] - (1.0/300.0_f64).sqrt()).abs() < 1e-6);
+    assert!((fit.r_squared().unwrap() - (1.0 - 0.1/40.1)).abs() < 1e-8);
+}
+
+#[test]
+fn test_curve_fit_degenerate_data() {
+    let line=|p:[f64;2], t:f64|p[0]+p[1]*t;
+    // equal data values have no coefficient of determination
+    let fit=curve_fit(line, [0.0, 0.0], &[0.0, 1.0, 2.0], &[1.0; 3], &[1.0; 3], None).unwrap();
+    assert!((fit.parameters()[0] - 1.0).abs() < 1e-8);
+    assert!(fit.r_squared().is_none());
+    // without degrees of freedom there is no estimate of the variance
+    let fit=curve_fit(line, [0.0, 0.0], &[0.0, 1.0], &[1.0, 3.0], &[1.0; 2], None).unwrap();
+    assert!(fit.reduced_chi_square().is_none());
+    assert!(fit.standard_errors().is_none());
+    assert!(matches!(curve_fit(line, [0.0, 0.0], &[0.0, 1.0, 2.0], &[1.0, 2.0, 3.0], &[1.0, 0.0, 1.0], None),
+                     Err(OptimizationError::NonpositiveStandardDeviation(1))));
+    assert!(matches!(curve_fit(line, [0.0, 0.0], &[], &[], &[], None),
+                     Err(OptimizationError::ContainerConstruct(_))));
+}
@@ -0,0 +1,119 @@
+// small helpers for the dynamic vectors and matrices used by the solvers
+
+use num_traits::{One, Zero};
+use algebra_traits::{CastFromf64, Conjugate, Norm, NormSquared, Scalar};
+use algebra::VectorDyn;
+use container_traits::{FromFn, Iter};
+use matrix::MatrixDyn;
+use matrix_traits::{MatrixTryConstruct, MatrixView};
+
+// a*v+b*w
+pub(crate) fn lin_comb<F:Scalar>(a:F, v:&VectorDyn<F>, b:F, w:&VectorDyn<F>) -> VectorDyn<F> {
+    VectorDyn::from_iter(
+        v.iter()
+         .zip(w.iter())
+         .map(|(vi,wi)|a.clone()*vi.clone()+b.clone()*wi.clone()))
+}
+
+pub(crate) fn scale<F:Scalar>(a:F, v:&VectorDyn<F>) -> VectorDyn<F> {
+    VectorDyn::from_iter(
+        v.iter()
+         .map(|vi|a.clone()*vi.clone()))
+}
+
+pub(crate) fn norm2<F:Scalar>(v:&VectorDyn<F>) -> F::RealType {
+    v.norm_squared()
+     .into_signed()
+}
+
+// sum |v_i|
+pub(crate) fn norm1<F:Scalar>(v:&VectorDyn<F>) -> F::RealType {
+    v.iter()
+     .fold(F::RealType::zero(),|acc,vi|acc+vi.norm().into_signed())
+}
+
+// sesquilinear scalar product sum conj(v_i)*w_i
+pub(crate) fn dot<F:Scalar>(v:&VectorDyn<F>, w:&VectorDyn<F>) -> F {
+    v.iter()
+     .zip(w.iter())
+     .fold(F::zero(),|acc,(vi,wi)|acc+vi.conjugate()*wi.clone())
+}
+
+// real part of the (sesquilinear) scalar product, computed by polarization
+pub(crate) fn re_dot<F:Scalar>(v:&VectorDyn<F>, w:&VectorDyn<F>) -> F::RealType {
+    let sum=lin_comb(F::one(), v, F::one(), w);
+    (norm2(&sum)-norm2(v)-norm2(w))*F::RealType::from_f64(0.5)
+}
+
+// m*v
+pub(crate) fn mat_vec<F:Scalar>(m:&MatrixDyn<F>, v:&VectorDyn<F>) -> VectorDyn<F> {
+    VectorDyn::from_iter(
+        (0..m.nrows()).map(|i|
+            (0..m.ncols()).fold(F::zero(),|acc,j|acc+m[(i,j)].clone()*v[j].clone())))
+}
+
+// m^H*v
+pub(crate) fn adjoint_mat_vec<F:Scalar>(m:&MatrixDyn<F>, v:&VectorDyn<F>) -> VectorDyn<F> {
+    VectorDyn::from_iter(
+        (0..m.ncols()).map(|j|
+            (0..m.nrows()).fold(F::zero(),|acc,i|acc+m[(i,j)].conjugate()*v[i].clone())))
+}
+
+// a*m+b*n
+pub(crate) fn mat_lin_comb<F:Scalar>(a:F, m:&MatrixDyn<F>, b:F, n:&MatrixDyn<F>) -> MatrixDyn<F> {
+    MatrixDyn::from_fn((m.nrows(), m.ncols()),|ij|a.clone()*m[ij].clone()+b.clone()*n[ij].clone())
+}
+
+// m^H
+pub(crate) fn adjoint<F:Scalar>(m:&MatrixDyn<F>) -> MatrixDyn<F> {
+    MatrixDyn::from_fn((m.ncols(), m.nrows()),|(i,j)|m[(j,i)].conjugate())
+}
+
+// a*b
+pub(crate) fn mat_mul<F:Scalar>(a:&MatrixDyn<F>, b:&MatrixDyn<F>) -> MatrixDyn<F> {
+    MatrixDyn::from_fn((a.nrows(), b.ncols()),|(i,j)|
+        (0..a.ncols()).fold(F::zero(),|acc,k|acc+a[(i,k)].clone()*b[(k,j)].clone()))
+}
+
+// a^H*b
+pub(crate) fn adjoint_mat_mul<F:Scalar>(a:&MatrixDyn<F>, b:&MatrixDyn<F>) -> MatrixDyn<F> {
+    MatrixDyn::from_fn((a.ncols(), b.ncols()),|(i,j)|
+        (0..a.nrows()).fold(F::zero(),|acc,k|acc+a[(k,i)].conjugate()*b[(k,j)].clone()))
+}
+
+// m^H*m
+pub(crate) fn gram<F:Scalar>(m:&MatrixDyn<F>) -> MatrixDyn<F> {
+    adjoint_mat_mul(m, m)
+}
+
+// the kkt matrix [[h, a^H], [a, 0]] of a quadratic program with the constraint matrix a
+pub(crate) fn kkt_matrix<F:Scalar>(h:&MatrixDyn<F>, a:&MatrixDyn<F>) -> MatrixDyn<F> {
+    let n=h.ncols();
+    let p=a.nrows();
+    MatrixDyn::from_fn((n+p, n+p),|(i,j)|
+        if i < n && j < n {
+            h[(i,j)].clone()
+        } else if i < n {
+            a[(j-n,i)].conjugate()
+        } else if j < n {
+            a[(i-n,j)].clone()
+        } else {
+            F::zero()
+        })
+}
+
+pub(crate) fn identity<F:Scalar>(n:usize) -> MatrixDyn<F> {
+    MatrixDyn::from_fn((n,n),|(i,j)|if i == j { F::one() } else { F::zero() })
+}
+
+// the k-th column of m
+pub(crate) fn col<F:Scalar>(m:&MatrixDyn<F>, k:usize) -> VectorDyn<F> {
+    VectorDyn::from_iter((0..m.nrows()).map(|i|m[(i,k)].clone()))
+}
+
+// least squares solution of a*x=b for every column of b
+pub(crate) fn try_solve_columns<F:Scalar>(a:&MatrixDyn<F>, b:&MatrixDyn<F>) -> Option<MatrixDyn<F>> {
+    let cols=(0..b.ncols()).map(|k|crate::try_solve_least_squares(a.clone(), col(b, k)))
+                           .collect::<Option<Vec<VectorDyn<F>>>>()?;
+    MatrixDyn::try_from_cols(cols.into_iter().map(|c|c.into())).ok()
+}
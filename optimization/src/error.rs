@@ -25,6 +25,30 @@ pub enum OptimizationError<F:'static, X, EX=LCCE> {
     #[error("Matrix {0} representing the derivative of the provided function at {1} does not have full rank")]
     MatrixNotFullRank(MatrixDyn<F>, X),
 
+    #[error("Lower bound of parameter {0} is larger than its upper bound")]
+    InconsistentBounds(usize),
+
+    #[error("First guess {0} does not satisfy the bounds")]
+    InfeasibleFirstGuess(X),
+
+    #[error("Inequality constraint {0} can not be satisfied together with the active constraints")]
+    InfeasibleConstraint(usize),
+
+    #[error("Slack of inequality constraint {0} vanished")]
+    VanishedSlack(usize),
+
+    #[error("Standard deviation of data point {0} is not positive")]
+    NonpositiveStandardDeviation(usize),
+
+    #[error("The constraints can not be satisfied")]
+    Infeasible,
+
+    #[error("The objective is unbounded on the feasible set")]
+    Unbounded,
+
+    #[error("No step along the search direction at {0} decreases the cost")]
+    NoDecrease(X),
+
     #[error("Problem creating optimization problem {0}")]
     ProblemBuilderError(#[from] ProblemBuilderError)
 }
\ No newline at end of file
@@ -1,30 +1,75 @@
-use algebra_traits::{NonZero, Scalar, TryDiv, TrySub, CastFromf64};
+use num_traits::One;
+use algebra_traits::{NonZero, Norm, Scalar, TryDiv, TrySub, CastFromf64};
+use algebra_traits::div_by_small_natural::Div2;
 
 #[derive(Clone, Copy, Debug)]
 pub enum FiniteDifferenceMethod {
     Forward,
     Backward,
     Centered,
+    // centered differences with the steps h, h/2, ..., h/2^n extrapolated to h=0,
+    // the error is of order h^(2n+2)
+    Richardson(u8),
+}
+
+impl FiniteDifferenceMethod {
+    // order of the truncation error
+    fn order(&self) -> i32 {
+        match self {
+            FiniteDifferenceMethod::Forward       => 1,
+            FiniteDifferenceMethod::Backward      => 1,
+            FiniteDifferenceMethod::Centered      => 2,
+            FiniteDifferenceMethod::Richardson(n) => 2*(*n as i32)+2,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum FiniteDifferenceStep<F> {
+    Fixed(NonZero<F>),
+    // max(1,|x|)*eps^(1/(order+1)) with the machine precision eps of f64,
+    // which balances the truncation error against the rounding error
+    Automatic,
 }
 
 #[derive(Clone, Copy, Debug)]
 pub struct FiniteDifference<F> {
-    step: NonZero<F>,
+    step: FiniteDifferenceStep<F>,
     fdm: FiniteDifferenceMethod,
 }
 
 impl<F> FiniteDifference<F> {
     pub fn new(step: NonZero<F>, fdm: FiniteDifferenceMethod) -> Self {
-        Self { step, fdm }
+        Self { step: FiniteDifferenceStep::Fixed(step), fdm }
+    }
+
+    pub fn automatic(fdm: FiniteDifferenceMethod) -> Self {
+        Self { step: FiniteDifferenceStep::Automatic, fdm }
     }
 
+    // the step used for the derivative at x
+    pub fn step_at(&self, x: &F) -> F where F:Scalar {
+        match &self.step {
+            FiniteDifferenceStep::Fixed(step) => step.clone().into_inner(),
+            FiniteDifferenceStep::Automatic   => {
+                let one=F::RealType::one();
+                let xn=x.norm().into_signed();
+                let scale=if xn > one { xn } else { one };
+                let base=f64::EPSILON.powf(1.0/(self.fdm.order()+1) as f64);
+                F::from(scale*F::RealType::from_f64(base))
+            },
+        }
+    }
+
+    // derivative of f at x where f(h) is the function at x+h and f0 its value at x
     pub fn apply<
-        Y: Clone+TrySub<Output = Y> + TryDiv<F>>(
+        Y: Clone+TrySub<Output = Y> + TryDiv<F, Output = Y>>(
         &self,
         f: impl Fn(F) -> Y,
         f0: &Y,
-    ) -> <Y as TryDiv<F>>::Output where F:Scalar {
-        let h = self.step.clone().into_inner();
+        x: &F,
+    ) -> Y where F:Scalar {
+        let h = self.step_at(x);
         let hc=||h.clone();
 
         let subdiv=|u:Y,v,w|u.try_sub(v).ok().unwrap()
@@ -33,15 +78,56 @@ impl<F> FiniteDifference<F> {
             FiniteDifferenceMethod::Forward =>  subdiv(f(hc()), f0.clone(), hc()),
             FiniteDifferenceMethod::Backward => subdiv(f0.clone(), f(-hc()), hc()),
             FiniteDifferenceMethod::Centered => subdiv(f(hc()), f(-hc()), hc()+hc()),
+            FiniteDifferenceMethod::Richardson(n) => {
+                let four=F::from(F::RealType::from_f64(4.0));
+                // rows of the extrapolation table, the j-th entry is free of the errors up to h^(2j)
+                let mut prev:Vec<Y>=Vec::new();
+                let mut h=hc();
+                for _ in 0..=n {
+                    let mut row=vec![subdiv(f(h.clone()), f(-h.clone()), h.clone()+h.clone())];
+                    let mut c=F::one();
+                    for p in prev.into_iter() {
+                        c=c*four.clone();
+                        let d=row.last().unwrap().clone();
+                        // d+(d-p)/(4^j-1)
+                        let correction=subdiv(p, d.clone(), c.clone()-F::one());
+                        row.push(d.try_sub(correction).ok().unwrap());
+                    }
+                    prev=row;
+                    h=h.div2();
+                }
+                prev.pop().unwrap()
+            },
         }
     }
 }
 
 impl<F:Scalar> Default for FiniteDifference<F> {
     fn default() -> Self {
-        Self {
-            step: NonZero::try_new(F::from(F::RealType::from_f64(1e-8))).unwrap(),
-            fdm: FiniteDifferenceMethod::Centered,
-        }
+        Self::new(NonZero::try_new(F::from(F::RealType::from_f64(1e-8))).unwrap(),
+                  FiniteDifferenceMethod::Centered)
+    }
+}
+
+#[test]
+fn test_richardson() {
+    // the centered difference with this step has an error of about 1e-3
+    let step=NonZero::try_new(0.1).unwrap();
+    let fd=FiniteDifference::new(step, FiniteDifferenceMethod::Richardson(3));
+    let x=1.3_f64;
+    let d=fd.apply(|h:f64|(x+h).exp(), &x.exp(), &x);
+    assert!((d - x.exp()).abs() < 1e-11);
+}
+
+#[test]
+fn test_automatic_step() {
+    let x=1e4_f64;
+    let f=|h:f64|(x+h).ln();
+    // relative errors
+    for (fdm, tol) in [(FiniteDifferenceMethod::Forward, 1e-6),
+                       (FiniteDifferenceMethod::Centered, 1e-10),
+                       (FiniteDifferenceMethod::Richardson(2), 1e-12)] {
+        let fd=FiniteDifference::automatic(fdm);
+        assert!((fd.apply(f, &x.ln(), &x)*x - 1.0).abs() < tol);
     }
 }
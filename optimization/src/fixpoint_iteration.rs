@@ -1,7 +1,17 @@
 // assuming we have a function we try to find the fixpoint and its a contraction
 
-use super::OptimizationError;
-use algebra_traits::{Nonnegative, Distance, TryDistance};
+use std::collections::VecDeque;
+use num_traits::One;
+use container_traits::{AnyParameters, LinearContainerConstructError as LCCE};
+
+use super::{from_dvec, into_dvec, OptimizationError};
+use algebra_traits::{Nonnegative, Distance, RealNumber, Scalar, TryDiv, TryDistance};
+
+use algebra::VectorDyn;
+
+use matrix::MatrixDyn;
+use matrix_traits::MatrixTryConstruct;
+use crate::dvec::lin_comb;
 
 
 // fixed number of iterations
@@ -69,3 +79,114 @@ pub fn fix_point_iteration_distance<X:Clone+Distance<DistT=D>, D : Clone+Partial
             maxiter)
 }
 
+
+// anderson acceleration with the given depth. the next iterate is the combination of the
+// last depth+1 function values whose residuals f(x)-x have the least squares minimal
+// combination. the history is restarted if the differences of the residuals become
+// linearly dependent, depth 0 is the plain iteration
+pub fn anderson_fix_point_iteration<F : Scalar,
+                                    X : AnyParameters<F,LCCE>+Clone>(
+    xstart:X,
+    f:impl Fn(X) -> X,
+    depth:usize,
+    fquite:impl Fn(X,X) -> Result<bool, OptimizationError<(), X>>,
+    maxiter:Option<u8>) -> Result<X,    OptimizationError<(), X>> {
+    let maxiter=maxiter.unwrap_or(10);
+    let f=|x:&VectorDyn<F>|into_dvec::<F,X>(f(from_dvec(x.clone())));
+    let mut x=into_dvec::<F,X>(xstart);
+    // differences of the residuals and of the function values of successive iterates
+    let mut history:VecDeque<(VectorDyn<F>,VectorDyn<F>)>=VecDeque::new();
+    let mut previous:Option<(VectorDyn<F>,VectorDyn<F>)>=None;
+    let mut iter=0;
+    while iter < maxiter {
+        let fx=f(&x);
+        let g=lin_comb(F::one(), &fx, -F::one(), &x);
+        if let Some((g_old, fx_old)) = previous.take() {
+            history.push_back((lin_comb(F::one(), &g,  -F::one(), &g_old),
+                               lin_comb(F::one(), &fx, -F::one(), &fx_old)));
+            if history.len() > depth {
+                history.pop_front();
+            }
+        }
+        previous=Some((g.clone(), fx.clone()));
+        let gamma=if history.is_empty() {
+            None
+        } else {
+            let dg=MatrixDyn::try_from_cols(history.iter().map(|(dg,_)|dg.clone().into())).unwrap();
+            crate::try_solve_least_squares(dg, g)
+        };
+        let xnew=match gamma {
+            Some(gamma) => history.iter()
+                                  .zip(gamma.iter())
+                                  .fold(fx, |acc,((_,df),gi)|lin_comb(F::one(), &acc, -gi.clone(), df)),
+            None => {
+                history.clear();
+                fx
+            },
+        };
+        let xold=std::mem::replace(&mut x, xnew);
+        if fquite(from_dvec(xold), from_dvec(x.clone()))? { break; }
+        iter+=1;
+    }
+    if iter == maxiter {
+        return Err(OptimizationError::MaximalIteration(iter));
+    }
+    Ok(from_dvec(x))
+}
+
+
+// aitken's Δ² extrapolation of x, f(x), f(f(x)), i.e. steffensen's method.
+// every iteration evaluates f twice
+pub fn aitken_fix_point_iteration<R:RealNumber>(
+    xstart:R,
+    f:impl Fn(R) -> R,
+    fquite:impl Fn(R,R) -> Result<bool, OptimizationError<(), R>>,
+    maxiter:Option<u8>) -> Result<R,    OptimizationError<(), R>> {
+    let maxiter=maxiter.unwrap_or(10);
+    let mut x=xstart;
+    let mut iter=0;
+    while iter < maxiter {
+        let x1=f(x.clone());
+        let x2=f(x1.clone());
+        let d1=x1.clone()-x.clone();
+        let denominator=x2.clone()-x1.clone()-d1.clone();
+        // the iterates lie on a line, e.g. at the fixpoint
+        let xnew=match (d1.clone()*d1).try_div(denominator) {
+            Ok(correction) => x.clone()-correction,
+            Err(_)         => x2,
+        };
+        let xold=std::mem::replace(&mut x, xnew);
+        if fquite(xold, x.clone())? { break; }
+        iter+=1;
+    }
+    if iter == maxiter {
+        return Err(OptimizationError::MaximalIteration(iter));
+    }
+    Ok(x)
+}
+
+#[cfg(test)]
+fn small_step(xold:[f64;3], x:[f64;3]) -> Result<bool, OptimizationError<(), [f64;3]>> {
+    Ok((0..3).all(|i|(x[i]-xold[i]).abs() < 1e-12))
+}
+
+#[test]
+fn test_anderson_linear_contraction() {
+    // spectral radius about 0.91
+    let a=[[0.9, 0.05, 0.0], [0.05, 0.5, 0.1], [0.0, 0.1, 0.2]];
+    let b=[1.0, -1.0, 0.5];
+    let f=|x:[f64;3]|std::array::from_fn(|i|(0..3).map(|j|a[i][j]*x[j]).sum::<f64>()+b[i]);
+    let plain=fix_point_iteration_with_termination_condition([0.0; 3], f, small_step, Some(30));
+    assert!(matches!(plain, Err(OptimizationError::MaximalIteration(30))));
+    let x=anderson_fix_point_iteration([0.0; 3], f, 3, small_step, Some(30)).unwrap();
+    let fx=f(x);
+    assert!((0..3).all(|i|(fx[i]-x[i]).abs() < 1e-10));
+}
+
+#[test]
+fn test_aitken_cosine() {
+    let quite=|xold:f64, x:f64|Ok((x-xold).abs() < 1e-14);
+    assert!(fix_point_iteration_with_termination_condition(1.0, f64::cos, quite, Some(10)).is_err());
+    let x=aitken_fix_point_iteration(1.0, f64::cos, quite, Some(10)).unwrap();
+    assert!((x - 0.7390851332151607).abs() < 1e-14);
+}
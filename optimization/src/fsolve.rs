@@ -16,6 +16,7 @@ pub fn fsolve<
         opts : Option<OptimizationOptions<F>>) -> Result<X, OptimizationError<F, X>> {
         ProblemBuilder::new(&f,x0)
            .set_target_to_zero()
+           .set_weights_to_one()
            .options(opts.unwrap_or_default())
            .build().unwrap()
            .solve()
@@ -31,6 +32,7 @@ pub fn fsolve_with_der<
         opts : Option<OptimizationOptions<F>>) -> Result<X, OptimizationError<F, X>> {
         ProblemBuilder::new(&f,x0)
             .set_target_to_zero()
+            .set_weights_to_one()
             .options(opts.unwrap_or_default())
             .build().unwrap()
             .solve_with_der(&fder)
@@ -46,6 +48,7 @@ pub fn solve_inverse_problem<
         opts : Option<OptimizationOptions<F>>) -> Result<X, OptimizationError<F, X>> {
         ProblemBuilder::new(&f,x0)
             .target(y)
+            .set_weights_to_one()
             .options(opts.unwrap_or_default())
             .build().unwrap()
             .solve()
@@ -62,6 +65,7 @@ pub fn solve_inverse_problem_with_der<
         opts : Option<OptimizationOptions<F>>) -> Result<X, OptimizationError<F,X>> {
         ProblemBuilder::new(&f,x0)
             .target(y)
+            .set_weights_to_one()
             .options(opts.unwrap_or_default())
             .build().unwrap()
             .solve_with_der(&fder)
@@ -0,0 +1,65 @@
+use algebra_traits::Scalar;
+
+use container_traits::{AnyParameters, IntoParameters, LinearContainerConstructError as LCCE};
+use super::{Bounds, OptimizationError, OptimizationOptions, ProblemBuilder};
+
+
+// solves s.t. every parameter of the solution stays within its bounds
+pub fn solve_inverse_problem_bounded<
+    F : Scalar,
+    X : Clone+AnyParameters<F,LCCE>,
+    Y : Clone+IntoParameters<F>>(
+        f      : impl Fn(X) -> Y,
+        x0     : X,
+        y      : Y,
+        bounds : Bounds<F::RealType>,
+        opts   : Option<OptimizationOptions<F>>) -> Result<X, OptimizationError<F, X>> {
+        ProblemBuilder::new(&f,x0)
+           .target(y)
+           .set_weights_to_one()
+           .bounds(bounds)
+           .options(opts.unwrap_or_default())
+           .build()?
+           .solve()
+}
+
+pub fn fsolve_bounded<
+    F : Scalar,
+    X : Clone+AnyParameters<F,LCCE>,
+    Y : Clone+AnyParameters<F,LCCE>>(
+        f      : impl Fn(X) -> Y,
+        x0     : X,
+        bounds : Bounds<F::RealType>,
+        opts   : Option<OptimizationOptions<F>>) -> Result<X, OptimizationError<F, X>> {
+    ProblemBuilder::new(&f,x0)
+           .set_target_to_zero()
+           .set_weights_to_one()
+           .bounds(bounds)
+           .options(opts.unwrap_or_default())
+           .build()?
+           .solve()
+}
+
+#[test]
+fn test_bounded_scalar() {
+    use algebra_traits::Interval;
+    let bounds=Bounds::from_intervals([Interval::try_new(0.0, 5.0).unwrap()]);
+    let xsol = solve_inverse_problem_bounded(|x:f64| x, 2.0, -1.0, bounds, None).unwrap();
+    assert_eq!(xsol, 0.0);
+}
+
+#[test]
+fn test_bounded_box() {
+    let f = |x: [f64;2]| [x[0] - 3.0, x[1] + 2.0];
+    let xsol = fsolve_bounded(f, [0.5, 0.5], Bounds::from_pair([0.0, 0.0], [1.0, 1.0]), None).unwrap();
+    assert_eq!(xsol, [1.0, 0.0]);
+}
+
+#[test]
+fn test_bounded_errors() {
+    let f = |x: [f64;2]| [x[0] - 3.0, x[1] + 2.0];
+    let res = fsolve_bounded(f, [2.0, 0.5], Bounds::from_pair([0.0, 0.0], [1.0, 1.0]), None);
+    assert!(matches!(res, Err(OptimizationError::InfeasibleFirstGuess(_))));
+    let res = fsolve_bounded(f, [0.5, 0.5], Bounds::from_pair([0.0, 2.0], [1.0, 1.0]), None);
+    assert!(matches!(res, Err(OptimizationError::InconsistentBounds(1))));
+}
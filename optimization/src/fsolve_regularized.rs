@@ -16,6 +16,7 @@ pub fn solve_inverse_problem_regularized<
         opts : Option<OptimizationOptions<F>>) -> Result<X, OptimizationError<F, X>> {
         ProblemBuilder::new(&f,x0)
            .target(y)
+           .set_weights_to_one()
            .options(opts.unwrap_or_default())
            .build().unwrap()
            .regularize(rc)
@@ -32,6 +33,7 @@ pub fn fsolve_regularized<
         opts : Option<OptimizationOptions<F>>) -> Result<X, OptimizationError<F, X>> {
     ProblemBuilder::new(&f,x0)
            .set_target_to_zero()
+           .set_weights_to_one()
            .options(opts.unwrap_or_default())
            .build().unwrap()
            .regularize(rc)
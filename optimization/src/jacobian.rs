@@ -1,12 +1,15 @@
 use super::{from_dvec, into_dvec, FiniteDifference};
 
-use container_traits::{Len, AnyParameters, ContainerConstructError, IntoParameters};
+use num_traits::{One, Zero};
+use super::dvec::{adjoint_mat_vec, lin_comb, norm2};
 
-use algebra_traits::Scalar;
-use algebra::VectorDyn;
+use container_traits::{Len, AnyFromParameters, AnyParameters, ContainerConstructError, FromFn, IntoParameters};
+
+use algebra_traits::{CastFromf64, Conjugate, NonZero, Nonnegative, Norm, RealAndImag, RealNumber, Scalar, Sqrt, TryDiv};
+use algebra::{Complex, Dual, VectorDyn};
 
 use matrix::MatrixDyn;
-use matrix_traits::MatrixTryConstruct;
+use matrix_traits::{MatrixTryConstruct, MatrixView};
 
 pub fn jacobian_dvec<F:Scalar>(
     f: impl Fn(VectorDyn<F>) -> VectorDyn<F>,
@@ -19,7 +22,7 @@ pub fn jacobian_dvec<F:Scalar>(
         x[i] += dpi;
         f(x)
     };
-    let cols=(0..(x0.len())).map(|i|fin_diff.apply(|dpi:F|f(i,dpi), &fx0).into());
+    let cols=(0..(x0.len())).map(|i|fin_diff.apply(|dpi:F|f(i,dpi), &fx0, &x0[i]).into());
     MatrixDyn::try_from_cols(cols).unwrap()
 }
 
@@ -36,24 +39,123 @@ pub fn jacobian<F : Scalar,
     jacobian_dvec(f, dvec, fin_diff)
 }
 
-// pub fn uncertainties<
-//     F : Scalar,
-//     X : Clone+Iter<F>+AnyFromIterator<F,ContainerConstructError<usize>>,
-//     Y : Clone+Iter<F>>(
-//     f: & impl Fn(&X) -> Y,
-//     x0: &X,
-//     fin_diff: FiniteDifference<F>,
-// ) -> Option<Vec<Nonnegative<F::RealType>>> {
-//     uncertainties_from_jacobian(jacobian(f, x0, fin_diff))
-// }
-
-// fn uncertainties_from_jacobian<F:Scalar>(m:MatrixDyn<F>) -> Option<Vec<Nonnegative<F::RealType>>> {
-//     Some(m.try_pseudo_inverse()?
-//           .into_rows()
-//           .into_iter()
-//           .map(|r|r.into_norm())
-//           .collect())
-// }
+// exact jacobian by forward mode automatic differentiation. f has to be generic in the
+// scalar such that it can be evaluated with dual numbers, e.g. model::<Dual<f64>>.
+// column j is the dual part of f at x0+e_j*ε
+pub fn jacobian_ad<F  : RealNumber,
+                   X  : IntoParameters<F>,
+                   XD : AnyFromParameters<Dual<F>,ContainerConstructError<usize>>,
+                   YD : IntoParameters<Dual<F>>>(
+    f: impl Fn(XD) -> YD,
+    x0: X,
+) -> MatrixDyn<F> {
+    let x0=into_dvec::<F,X>(x0);
+    let seed=|j:usize|x0.iter()
+                        .enumerate()
+                        .map(move |(i,xi)|Dual::new(xi.clone(), if i == j { F::one() } else { F::zero() }));
+    let cols=(0..(x0.len())).map(|j|{
+        let fx=f(XD::any_from_iter(None, seed(j)).ok().unwrap());
+        VectorDyn::from_iter(fx.into_parameters()
+                               .map(|y|{
+                                   let [_,eps]=y.into_re_eps();
+                                   eps
+                               })).into()
+    });
+    MatrixDyn::try_from_cols(cols).unwrap()
+}
+
+// the hessian of Re(v^H*g(x)) by finite differences of a(x)^H*v, where a is the derivative of g,
+// hermitian by construction. if a is itself computed by finite differences, the step of fin_diff
+// has to be large compared to the step used for a
+pub(crate) fn adjoint_product_hessian<F : Scalar,
+                                      X : AnyParameters<F,ContainerConstructError<usize>>+Clone>(
+        derivative : &impl Fn(X) -> MatrixDyn<F>,
+        x          : &X,
+        v          : &VectorDyn<F>,
+        fin_diff   : FiniteDifference<F>) -> MatrixDyn<F> {
+    let hessian=jacobian_dvec(|y|adjoint_mat_vec(&derivative(from_dvec(y)), v),
+                              into_dvec(x.clone()),
+                              fin_diff);
+    let half=F::from(F::RealType::from_f64(0.5));
+    MatrixDyn::from_fn((hessian.nrows(), hessian.ncols()),|(i,j)|
+        (hessian[(i,j)].clone()+hessian[(j,i)].conjugate())*half.clone())
+}
+
+// jacobian by the complex step method. f has to be generic in the scalar and real analytic,
+// such that f(x0+e_j*ih)=f(x0)+J*e_j*ih+O(h^2). there is no cancellation as for finite
+// differences, such that tiny steps, e.g. 1e-20, give the jacobian exact up to rounding
+pub fn jacobian_complex_step<R  : RealNumber,
+                             X  : IntoParameters<R>,
+                             XC : AnyFromParameters<Complex<R>,ContainerConstructError<usize>>,
+                             YC : IntoParameters<Complex<R>>>(
+    f: impl Fn(XC) -> YC,
+    x0: X,
+    step: NonZero<R>,
+) -> MatrixDyn<R> {
+    let h=&step.into_inner();
+    let x0=into_dvec::<R,X>(x0);
+    let shift=|j:usize|x0.iter()
+                         .enumerate()
+                         .map(move |(i,xi)|Complex::new(xi.clone(), if i == j { h.clone() } else { R::zero() }));
+    let cols=(0..(x0.len())).map(|j|{
+        let fx=f(XC::any_from_iter(None, shift(j)).ok().unwrap());
+        VectorDyn::from_iter(fx.into_parameters()
+                               .map(|y|y.into_imag().try_div(h.clone()).unwrap())).into()
+    });
+    MatrixDyn::try_from_cols(cols).unwrap()
+}
+
+// covariance of the parameters of a least squares solution and
+// the standard deviations and correlations derived from it.
+// the correlations are undefined if a standard deviation vanishes, e.g. for an exact fit
+#[derive(Clone, Debug, derive_getters::Getters)]
+pub struct Uncertainties<F:Scalar> {
+    covariance: MatrixDyn<F>,
+    standard_deviations: Vec<Nonnegative<F::RealType>>,
+    correlations: Option<MatrixDyn<F>>,
+}
+
+// f(x0) is the least squares fit to y
+pub fn uncertainties<
+    F : Scalar,
+    X : Clone+AnyParameters<F,ContainerConstructError<usize>>,
+    Y : Clone+IntoParameters<F>>(
+    f: impl Fn(X) -> Y,
+    x0: X,
+    y: Y,
+    fin_diff: FiniteDifference<F>,
+) -> Option<Uncertainties<F>> {
+    let res=lin_comb(F::one(), &into_dvec(y), -F::one(), &into_dvec(f(x0.clone())));
+    uncertainties_from_jacobian(jacobian(&f, x0, fin_diff), res)
+}
+
+// m and res are the weighted jacobian and residual at the solution.
+// the covariance (m^H*m)^-1 is scaled by the residual variance |res|^2/(#residuals-#parameters),
+// returns None if m does not have full rank or there are not more residuals than parameters
+pub fn uncertainties_from_jacobian<F:Scalar>(m:MatrixDyn<F>, res:VectorDyn<F>) -> Option<Uncertainties<F>> {
+    let (nr,nc)=(m.nrows(), m.ncols());
+    if nr <= nc || res.len() != nr {
+        return None;
+    }
+    let variance=norm2(&res).try_div(F::RealType::from_f64((nr-nc) as f64)).unwrap();
+    let normal=MatrixDyn::<F>::from_fn((nc,nc),|(i,j)|
+        (0..nr).fold(F::zero(),|acc,k|acc+m[(k,i)].conjugate()*m[(k,j)].clone()));
+    let unit=|j:usize|VectorDyn::from_iter((0..nc).map(|i|if i == j { F::one() } else { F::zero() }));
+    let cols=(0..nc).map(|j|crate::try_solve_least_squares(normal.clone(), unit(j)))
+                    .collect::<Option<Vec<VectorDyn<F>>>>()?;
+    let covariance=MatrixDyn::<F>::from_fn((nc,nc),|(i,j)|cols[j][i].clone()*variance.clone());
+    let standard_deviations:Vec<Nonnegative<F::RealType>>=
+        (0..nc).map(|i|covariance[(i,i)].norm().sqrt())
+               .collect();
+    let correlations=(0..nc).flat_map(|i|(0..nc).map(move |j|(i,j)))
+                            .map(|(i,j)|{
+                                let sd=standard_deviations[i].clone()*standard_deviations[j].clone();
+                                covariance[(i,j)].clone().try_div(sd.into_signed()).ok()
+                            })
+                            .collect::<Option<Vec<F>>>()
+                            .map(|c|MatrixDyn::<F>::from_fn((nc,nc),|(i,j)|c[i*nc+j].clone()));
+    Some(Uncertainties { covariance, standard_deviations, correlations })
+}
 
 #[cfg(test)]
 use algebra_traits::TryMaxNormOfEntries;
@@ -83,3 +185,44 @@ fn test_jacobian_lin() {
     let jac = Matrix::<f64,2,3>::try_from_matrix(jac).ok().unwrap();
     assert!((jac - m).try_max_norm_of_entries().unwrap() < 1e-6);
 }
+
+#[cfg(test)]
+fn analytic_test_function<S:Scalar>(x:[S;2]) -> Vec<S> {
+    use algebra_traits::{Exp, TrigonometricFunctions};
+    let [x0, x1]=x;
+    vec![x0.clone()*x1.clone(), x0.clone().sin()*x1.clone().exp(), x1.clone()*x1]
+}
+
+#[cfg(test)]
+fn check_analytic_test_jacobian(jac:MatrixDyn<f64>, x:[f64;2]) {
+    let expected=[[x[1],                  x[0]                 ],
+                  [x[0].cos()*x[1].exp(), x[0].sin()*x[1].exp()],
+                  [0.0,                   2.0*x[1]             ]];
+    for i in 0..3 {
+        for j in 0..2 {
+            assert!((jac[(i,j)] - expected[i][j]).abs() < 1e-14);
+        }
+    }
+}
+
+#[test]
+fn test_jacobian_ad() {
+    let x=[0.3, -1.2];
+    check_analytic_test_jacobian(jacobian_ad(analytic_test_function::<Dual<f64>>, x), x);
+}
+
+#[test]
+fn test_jacobian_complex_step() {
+    let x=[0.3, -1.2];
+    let step=NonZero::try_new(1e-20).unwrap();
+    check_analytic_test_jacobian(jacobian_complex_step(analytic_test_function::<Complex<f64>>, x, step), x);
+}
+
+#[test]
+fn test_uncertainties_of_exact_fit() {
+    // the residual of a line through three collinear points vanishes
+    let m=MatrixDyn::from_fn((3,2),|(i,j)|if j == 0 { 1.0 } else { i as f64 });
+    let unc=uncertainties_from_jacobian(m, VectorDyn::from_iter([0.0, 0.0, 0.0])).unwrap();
+    assert!(unc.standard_deviations().iter().all(|sd|sd.clone().into_signed() == 0.0));
+    assert!(unc.correlations().is_none());
+}
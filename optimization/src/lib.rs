@@ -2,28 +2,73 @@ pub mod error;
 pub use error::OptimizationError;
 
 pub mod finite_difference;
-pub use finite_difference::{FiniteDifference, FiniteDifferenceMethod};
+pub use finite_difference::{FiniteDifference, FiniteDifferenceMethod, FiniteDifferenceStep};
 
 pub mod jacobian;
-pub use jacobian::{jacobian, jacobian_dvec}; // , uncertainties
+pub use jacobian::{jacobian, jacobian_ad, jacobian_complex_step, jacobian_dvec, uncertainties, uncertainties_from_jacobian, Uncertainties};
+
+pub mod sparse_jacobian;
+pub use sparse_jacobian::{column_groups, into_dense, sparse_jacobian, sparse_jacobian_dvec};
 
 pub mod least_squares;
 pub use least_squares::try_solve_least_squares;
 
 pub mod fixpoint_iteration;
-pub use fixpoint_iteration::fix_point_iteration;
+pub use fixpoint_iteration::{anderson_fix_point_iteration, aitken_fix_point_iteration, fix_point_iteration};
+
+pub mod linear_programming;
+pub use linear_programming::{linear_program, LpSolution};
+
+pub mod quadratic_programming;
+pub use quadratic_programming::{quadratic_program, QpSolution};
+
+pub mod scalar_minimization;
+pub use scalar_minimization::{brent, golden_section, ScalarMinimum};
+
+pub mod quasi_newton;
+pub use quasi_newton::{quasi_newton, quasi_newton_with_gradient};
+
+pub mod nelder_mead;
+pub use nelder_mead::{nelder_mead, nelder_mead_report};
+
+pub mod riemannian_newton;
+pub use riemannian_newton::{riemannian_newton, riemannian_newton_with_gradient};
 
 pub mod fsolve;
 pub use fsolve::{fsolve, solve_inverse_problem};
 
 pub mod options;
-pub use options::{OptimizationOptions, OptimizationOptionsBuilder};
+pub use options::{BroydenOptions, BroydenVariant, JacobianUpdate, LevenbergMarquardtOptions, LineSearchOptions, Method, OptimizationOptions, OptimizationOptionsBuilder,
+                  QuasiNewtonMethod, QuasiNewtonOptions, QuasiNewtonOptionsBuilder, StepControl, TrustRegionOptions, WolfeOptions};
 
 pub mod fsolve_regularized;
 pub use fsolve_regularized::{fsolve_regularized, solve_inverse_problem_regularized};
 
+pub mod bounds;
+pub use bounds::Bounds;
+
+pub mod fsolve_bounded;
+pub use fsolve_bounded::{fsolve_bounded, solve_inverse_problem_bounded};
+
+pub mod odr;
+pub use odr::orthogonal_distance_regression;
+
+pub mod curve_fit;
+pub use curve_fit::{curve_fit, CurveFit};
+
+pub mod report;
+pub use report::{Iteration, Observer, SolveReport, TerminationReason};
+
+pub mod robust_loss;
+pub use robust_loss::RobustLoss;
+
 pub mod problem;
 pub use problem::{Problem, ProblemBuilder, ProblemBuilderError};
+pub use problem::constrained::ConstrainedProblem;
+pub use problem::interior_point::{InequalityConstrainedProblem, InteriorPointReport};
+pub use problem::whitening::Whitening;
+
+mod dvec;
 
 use algebra::VectorDyn;
 use container_traits::{AnyFromParameters, IntoParameters, LinearContainerConstructError};
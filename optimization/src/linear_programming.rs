@@ -0,0 +1,233 @@
+// dense linear programs
+// min c^T*x subject to a_ub*x<=b_ub, a_eq*x=b_eq and x>=0
+// solved by the two phase simplex method on a tableau. every row gets an artificial variable,
+// phase one minimizes their sum to find a feasible basis, phase two the objective.
+// the entering and leaving variables are chosen by bland's rule, which excludes cycling
+use container_traits::{FromFn, Iter, Len, LenNotEqualToRequiredLenError};
+
+use algebra::VectorDyn;
+
+use matrix::MatrixDyn;
+use matrix_traits::MatrixView;
+use super::OptimizationError;
+
+const TOL:f64=1e-10;
+
+// the dual values are the derivatives of the optimal objective with respect to b_ub and b_eq
+#[derive(Clone, Debug, derive_getters::Getters)]
+pub struct LpSolution {
+    x: VectorDyn<f64>,
+    objective: f64,
+    inequality_duals: VectorDyn<f64>,
+    equality_duals: VectorDyn<f64>,
+}
+
+impl LpSolution {
+    pub fn into_x(self) -> VectorDyn<f64> {
+        self.x
+    }
+}
+
+// the rows of the constraints followed by the right hand side, the basis holds the
+// column of the basic variable of every row
+struct Tableau {
+    rows: Vec<Vec<f64>>,
+    basis: Vec<usize>,
+}
+
+impl Tableau {
+    fn rhs(&self, i:usize) -> f64 {
+        *self.rows[i].last().unwrap()
+    }
+
+    fn pivot(&mut self, r:usize, e:usize) {
+        let p=self.rows[r][e];
+        self.rows[r].iter_mut().for_each(|v|*v/=p);
+        let pivot_row=self.rows[r].clone();
+        for (i, row) in self.rows.iter_mut().enumerate() {
+            let f=row[e];
+            if i != r && f != 0.0 {
+                row.iter_mut()
+                   .zip(pivot_row.iter())
+                   .for_each(|(v,pv)|*v-=f*pv);
+            }
+        }
+        self.basis[r]=e;
+    }
+
+    // c_B^T*B^-1 applied to column j
+    fn basic_cost(&self, cost:&[f64], j:usize) -> f64 {
+        self.rows
+            .iter()
+            .zip(self.basis.iter())
+            .map(|(row,b)|cost[*b]*row[j])
+            .sum()
+    }
+
+    // only the first allowed columns may enter the basis, returns false if the objective is unbounded
+    fn simplex(&mut self, cost:&[f64], allowed:usize) -> bool {
+        loop {
+            // bland: the first column with negative reduced cost enters
+            let entering=(0..allowed).find(|j|cost[*j]-self.basic_cost(cost, *j) < -TOL);
+            let e=match entering {
+                Some(e) => e,
+                None => { return true; }
+            };
+            // bland: ties of the ratio test are broken by the smallest basic variable
+            let leaving=(0..self.rows.len())
+                .filter(|i|self.rows[*i][e] > TOL)
+                .map(|i|(i, self.rhs(i)/self.rows[i][e]))
+                .fold(None,|acc:Option<(usize,f64)>,(i,ratio)|match acc {
+                    Some((k, rmin)) if rmin < ratio-TOL
+                                    || (rmin <= ratio+TOL && self.basis[k] < self.basis[i]) => acc,
+                    _ => Some((i, ratio)),
+                });
+            match leaving {
+                Some((r, _)) => self.pivot(r, e),
+                None => { return false; },
+            }
+        }
+    }
+}
+
+pub fn linear_program(
+    c:VectorDyn<f64>,
+    a_ub:MatrixDyn<f64>,
+    b_ub:VectorDyn<f64>,
+    a_eq:MatrixDyn<f64>,
+    b_eq:VectorDyn<f64>) -> Result<LpSolution, OptimizationError<f64,VectorDyn<f64>>> {
+    let n=c.len();
+    for len in [a_ub.ncols(), a_eq.ncols()] {
+        if len != n {
+            return Err(OptimizationError::ContainerConstruct(LenNotEqualToRequiredLenError::new(n, len).into()));
+        }
+    }
+    for (m, rhs) in [(&a_ub, &b_ub), (&a_eq, &b_eq)] {
+        if rhs.len() != m.nrows() {
+            return Err(OptimizationError::ContainerConstruct(LenNotEqualToRequiredLenError::new(m.nrows(), rhs.len()).into()));
+        }
+    }
+    let m_ub=a_ub.nrows();
+    let m=m_ub+a_eq.nrows();
+    // the columns are x, the slacks of the inequalities and the artificial variables
+    let nslack=n+m_ub;
+    let ncols=nslack+m;
+    // rows with negative right hand side are negated
+    let mut sign=vec![1.0; m];
+    let rows=(0..m).map(|i|{
+        let mut row=vec![0.0; ncols+1];
+        let rhs=if i < m_ub {
+            for (j, rj) in row.iter_mut().take(n).enumerate() {
+                *rj=a_ub[(i,j)];
+            }
+            row[n+i]=1.0;
+            b_ub[i]
+        } else {
+            for (j, rj) in row.iter_mut().take(n).enumerate() {
+                *rj=a_eq[(i-m_ub,j)];
+            }
+            b_eq[i-m_ub]
+        };
+        row[ncols]=rhs;
+        if rhs < 0.0 {
+            row.iter_mut().for_each(|v|*v=-*v);
+            sign[i]=-1.0;
+        }
+        row[nslack+i]=1.0;
+        row
+    }).collect();
+    let mut tableau=Tableau{rows, basis:(nslack..ncols).collect()};
+
+    let phase_one:Vec<f64>=(0..ncols).map(|j|if j < nslack { 0.0 } else { 1.0 }).collect();
+    tableau.simplex(&phase_one, ncols);
+    if tableau.basic_cost(&phase_one, ncols) > TOL {
+        return Err(OptimizationError::Infeasible);
+    }
+    // artificial variables remaining in the basis are zero and replaced if possible,
+    // otherwise their row is redundant
+    for i in 0..m {
+        let artificial=tableau.basis[i] >= nslack;
+        if let Some(j) = (0..nslack).find(|j|artificial && tableau.rows[i][*j].abs() > TOL) {
+            tableau.pivot(i, j);
+        }
+    }
+
+    let phase_two:Vec<f64>=(0..ncols).map(|j|if j < n { c[j] } else { 0.0 }).collect();
+    if !tableau.simplex(&phase_two, nslack) {
+        return Err(OptimizationError::Unbounded);
+    }
+    let mut x=VectorDyn::from_iter((0..n).map(|_|0.0));
+    for (i, b) in tableau.basis.iter().enumerate() {
+        if *b < n {
+            x[*b]=tableau.rhs(i);
+        }
+    }
+    // the columns of the artificial variables hold the inverse of the basis
+    let duals:Vec<f64>=(0..m).map(|i|sign[i]*tableau.basic_cost(&phase_two, nslack+i))
+                             .collect();
+    Ok(LpSolution{objective:tableau.basic_cost(&phase_two, ncols),
+                  x,
+                  inequality_duals:VectorDyn::from_iter(duals.iter().take(m_ub).cloned()),
+                  equality_duals:VectorDyn::from_iter(duals.iter().skip(m_ub).cloned())})
+}
+
+#[cfg(test)]
+fn no_rows(n:usize) -> (MatrixDyn<f64>, VectorDyn<f64>) {
+    (MatrixDyn::from_fn((0,n),|_|0.0), VectorDyn::from_iter([]))
+}
+
+#[test]
+fn test_lp_inequalities() {
+    // max 3x+5y subject to x<=4, 2y<=12, 3x+2y<=18
+    let a=[[1.0, 0.0], [0.0, 2.0], [3.0, 2.0]];
+    let (a_eq, b_eq)=no_rows(2);
+    let sol=linear_program(VectorDyn::from_iter([-3.0, -5.0]),
+                           MatrixDyn::from_fn((3,2),|(i,j)|a[i][j]),
+                           VectorDyn::from_iter([4.0, 12.0, 18.0]),
+                           a_eq, b_eq).unwrap();
+    assert!((sol.x()[0] - 2.0).abs() < 1e-10);
+    assert!((sol.x()[1] - 6.0).abs() < 1e-10);
+    assert!((sol.objective() + 36.0).abs() < 1e-10);
+    for (dual, expected) in sol.inequality_duals().iter().zip([0.0, -1.5, -1.0]) {
+        assert!((dual - expected).abs() < 1e-10);
+    }
+}
+
+#[test]
+fn test_lp_equality_with_negative_rhs() {
+    let (a_ub, b_ub)=no_rows(2);
+    let sol=linear_program(VectorDyn::from_iter([1.0, 1.0]),
+                           a_ub, b_ub,
+                           MatrixDyn::from_fn((1,2),|(_,j)|if j == 0 { 1.0 } else { -1.0 }),
+                           VectorDyn::from_iter([-1.0])).unwrap();
+    assert!(sol.x()[0].abs() < 1e-10);
+    assert!((sol.x()[1] - 1.0).abs() < 1e-10);
+    assert!((sol.equality_duals()[0] + 1.0).abs() < 1e-10);
+}
+
+#[test]
+fn test_lp_beale_cycling() {
+    // cycles with the largest coefficient rule
+    let a=[[0.25, -60.0, -0.04, 9.0], [0.5, -90.0, -0.02, 3.0], [0.0, 0.0, 1.0, 0.0]];
+    let (a_eq, b_eq)=no_rows(4);
+    let sol=linear_program(VectorDyn::from_iter([-0.75, 150.0, -0.02, 6.0]),
+                           MatrixDyn::from_fn((3,4),|(i,j)|a[i][j]),
+                           VectorDyn::from_iter([0.0, 0.0, 1.0]),
+                           a_eq, b_eq).unwrap();
+    assert!((sol.objective() + 0.05).abs() < 1e-10);
+    assert!((sol.x()[0] - 0.04).abs() < 1e-10);
+    assert!((sol.x()[2] - 1.0).abs() < 1e-10);
+}
+
+#[test]
+fn test_lp_infeasible_and_unbounded() {
+    let one_row=|a:[f64;2], b:f64|(MatrixDyn::from_fn((1,2),|(_,j)|a[j]), VectorDyn::from_iter([b]));
+    let (a_ub, b_ub)=one_row([1.0, 1.0], 1.0);
+    let (a_eq, b_eq)=one_row([1.0, 1.0], 3.0);
+    let res=linear_program(VectorDyn::from_iter([1.0, 1.0]), a_ub, b_ub, a_eq, b_eq);
+    assert!(matches!(res, Err(OptimizationError::Infeasible)));
+    let (a_ub, b_ub)=one_row([1.0, -1.0], 1.0);
+    let (a_eq, b_eq)=no_rows(2);
+    let res=linear_program(VectorDyn::from_iter([-1.0, 0.0]), a_ub, b_ub, a_eq, b_eq);
+    assert!(matches!(res, Err(OptimizationError::Unbounded)));
+}
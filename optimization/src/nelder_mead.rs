@@ -0,0 +1,203 @@
+// derivative free minimization by the nelder-mead simplex method. the coefficients
+// for reflection, expansion, contraction and shrinking are adapted to the dimension,
+// see Gao and Han, Implementing the Nelder-Mead simplex algorithm with adaptive parameters
+
+use std::cmp::Ordering;
+use num_traits::{One, Zero};
+use container_traits::{AnyParameters, Len, LenTooSmallError, LinearContainerConstructError as LCCE};
+
+use algebra_traits::{CastFromf64, Norm, RealNumber, TryDiv};
+
+use algebra::VectorDyn;
+
+use crate::dvec::lin_comb;
+use crate::report::{Evaluations, History};
+use super::{from_dvec, into_dvec, OptimizationError, OptimizationOptions, SolveReport, TerminationReason};
+
+// the initial simplex has the vertices x0 and x0+h_i*e_i
+fn initial_simplex<F:RealNumber>(x0:&VectorDyn<F>) -> Vec<VectorDyn<F>> {
+    let mut simplex=vec![x0.clone()];
+    for i in 0..x0.len() {
+        let mut x=x0.clone();
+        x[i]=if x[i].is_zero() { F::from_f64(2.5e-4) } else { x[i].clone()*F::from_f64(1.05) };
+        simplex.push(x);
+    }
+    simplex
+}
+
+// total order of the function values in which nan is larger than every number,
+// such that a vertex with an undefined function value is always the worst one
+fn compare<F:PartialOrd>(a:&F, b:&F) -> Ordering {
+    a.partial_cmp(b)
+     .unwrap_or_else(||a.partial_cmp(a).is_none().cmp(&b.partial_cmp(b).is_none()))
+}
+
+fn less<F:PartialOrd>(a:&F, b:&F) -> bool {
+    compare(a, b) == Ordering::Less
+}
+
+// the algorithm stops if the function values of the simplex differ by less than
+// the target cost of the options or if the simplex is smaller than the target cost.
+// for every iteration the smallest function value and the size of the simplex are recorded.
+// there has to be at least one parameter
+pub fn nelder_mead_report<F : RealNumber,
+                          X : AnyParameters<F,LCCE>+Clone>(
+    f:impl Fn(X) -> F,
+    x0:X,
+    opts:Option<OptimizationOptions<F>>) -> Result<SolveReport<F,X>, OptimizationError<F,X>> {
+    let opts=opts.unwrap_or_default();
+    let evaluations=Evaluations::default();
+    let f=|x:&VectorDyn<F>|{
+        evaluations.count_function();
+        f(from_dvec(x.clone()))
+    };
+    let x0:VectorDyn<F>=into_dvec(x0);
+    let n=x0.len();
+    if n == 0 {
+        return Err(OptimizationError::ContainerConstruct(LenTooSmallError::new(1, 0).into()));
+    }
+    let nf=F::from_f64(n as f64);
+    // the adaptive coefficients coincide with the standard ones for n=2
+    let na=F::from_f64(n.max(2) as f64);
+    let alpha=F::one();
+    let beta=F::one()+F::from_f64(2.0).try_div(na.clone()).unwrap();
+    let gamma=F::from_f64(0.75)-F::from_f64(0.5).try_div(na.clone()).unwrap();
+    let delta=F::one()-F::one().try_div(na).unwrap();
+    // affine combination a*v+(1-a)*w
+    let affine=|a:F, v:&VectorDyn<F>, w:&VectorDyn<F>|lin_comb(a.clone(), v, F::one()-a, w);
+
+    let mut simplex:Vec<(VectorDyn<F>,F)>=
+        initial_simplex(&x0).into_iter()
+                            .map(|x|{
+                                let fx=f(&x);
+                                (x, fx)
+                            })
+                            .collect();
+    let mut history=History::new(None);
+    let mut iter:u8=0;
+    while &iter < opts.max_iter() {
+        simplex.sort_by(|(_,fa),(_,fb)|compare(fa, fb));
+        let (best, fbest)=simplex[0].clone();
+        let spread=simplex[n].1.clone()-fbest.clone();
+        let size=simplex.iter()
+                        .map(|(x,_)|lin_comb(F::one(), x, -F::one(), &best).norm().into_signed())
+                        .fold(F::zero(),|acc,d|if d > acc { d } else { acc });
+        if &spread < opts.target_cost() {
+            return Ok(history.finish(from_dvec(best), TerminationReason::CostTolerance, &evaluations));
+        }
+        if &size < opts.target_cost() {
+            return Ok(history.finish(from_dvec(best), TerminationReason::StepTolerance, &evaluations));
+        }
+        let centroid=VectorDyn::from_iter(
+            (0..n).map(|i|simplex[..n].iter()
+                                      .fold(F::zero(),|acc,(x,_)|acc+x[i].clone())
+                                      .try_div(nf.clone()).unwrap()));
+        let (worst, fworst)=simplex[n].clone();
+        let fsecond=simplex[n-1].1.clone();
+        let xr=affine(F::one()+alpha.clone(), &centroid, &worst);
+        let fr=f(&xr);
+        let replacement=if less(&fr, &fbest) {
+            let xe=affine(beta.clone(), &xr, &centroid);
+            let fe=f(&xe);
+            Some(if less(&fe, &fr) { (xe, fe) } else { (xr, fr) })
+        } else if less(&fr, &fsecond) {
+            Some((xr, fr))
+        } else if less(&fr, &fworst) {
+            let xc=affine(gamma.clone(), &xr, &centroid);
+            let fc=f(&xc);
+            (!less(&fr, &fc)).then_some((xc, fc))
+        } else {
+            let xc=affine(gamma.clone(), &worst, &centroid);
+            let fc=f(&xc);
+            less(&fc, &fworst).then_some((xc, fc))
+        };
+        match replacement {
+            Some(vertex) => { simplex[n]=vertex; },
+            None => {
+                // shrink towards the best vertex
+                for vertex in simplex[1..].iter_mut() {
+                    let x=affine(delta.clone(), &vertex.0, &best);
+                    let fx=f(&x);
+                    *vertex=(x, fx);
+                }
+            },
+        }
+        history.record(&from_dvec::<F,X>(best), fbest, size);
+        iter+=1;
+    }
+    simplex.sort_by(|(_,fa),(_,fb)|compare(fa, fb));
+    Ok(history.finish(from_dvec(simplex[0].0.clone()), TerminationReason::MaxIterations, &evaluations))
+}
+
+pub fn nelder_mead<F : RealNumber,
+                   X : AnyParameters<F,LCCE>+Clone>(
+    f:impl Fn(X) -> F,
+    x0:X,
+    opts:Option<OptimizationOptions<F>>) -> Result<X, OptimizationError<F,X>> {
+    let report=nelder_mead_report(f, x0, opts)?;
+    match report.termination() {
+        TerminationReason::MaxIterations => Err(OptimizationError::MaximalIteration(report.iterations() as u8)),
+        _                                => Ok(report.into_solution()),
+    }
+}
+
+#[cfg(test)]
+fn nm_options() -> OptimizationOptions<f64> {
+    crate::OptimizationOptionsBuilder::default()
+        .target_cost(1e-12)
+        .max_iter(255)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_nelder_mead_non_smooth() {
+    // piecewise constant in x[0] on a fine grid, like a lookup table
+    let f = |x: [f64;2]| ((x[0]-1.0)*1e4).round().abs()*1e-4+(x[1]+0.5).abs();
+    let xsol = nelder_mead_report(f, [0.0, 0.0], Some(nm_options())).unwrap()
+                                                                   .into_solution();
+    assert!((xsol[0] - 1.0).abs() < 1e-3);
+    assert!((xsol[1] + 0.5).abs() < 1e-3);
+}
+
+#[test]
+fn test_nelder_mead_quadratic() {
+    let f = |x: [f64;3]| x.iter()
+                          .enumerate()
+                          .map(|(i,xi)|(i as f64+1.0)*(xi-i as f64).powi(2))
+                          .sum::<f64>();
+    let report = nelder_mead_report(f, [1.0, 1.0, 1.0], Some(nm_options())).unwrap();
+    for (i,xi) in report.solution().iter().enumerate() {
+        assert!((xi - i as f64).abs() < 1e-3);
+    }
+    assert!(report.history()
+                  .windows(2)
+                  .all(|w|w[1].cost() <= w[0].cost()));
+}
+
+#[test]
+fn test_nelder_mead_max_iter() {
+    let f = |x: [f64;2]| x[0]*x[0]+x[1]*x[1];
+    let opts = crate::OptimizationOptionsBuilder::default()
+        .max_iter(3)
+        .build()
+        .unwrap();
+    assert!(matches!(nelder_mead(f, [1.0, 1.0], Some(opts)), Err(OptimizationError::MaximalIteration(3))));
+}
+
+#[test]
+fn test_nelder_mead_nan_is_worst() {
+    // undefined for negative x[0], the initial simplex is close to this region
+    let f = |x: [f64;2]| (x[0].sqrt()-1.0).powi(2)+(x[1]-2.0).powi(2);
+    let xsol = nelder_mead(f, [0.01, 0.0], Some(nm_options())).unwrap();
+    assert!((xsol[0] - 1.0).abs() < 1e-3);
+    assert!((xsol[1] - 2.0).abs() < 1e-3);
+    assert_eq!(compare(&f64::NAN, &1.0), Ordering::Greater);
+    assert_eq!(compare(&1.0, &f64::NAN), Ordering::Less);
+}
+
+#[test]
+fn test_nelder_mead_no_parameters() {
+    let f = |_: [f64;0]| 1.0;
+    assert!(matches!(nelder_mead(f, [], None), Err(OptimizationError::ContainerConstruct(_))));
+}
@@ -0,0 +1,137 @@
+// orthogonal distance regression, the inputs t_i of the observations (t_i, y_i) are noisy as well.
+// the corrected inputs t_i+delta_i are nuisance parameters and
+// sum |w_y*(y_i-f(beta, t_i+delta_i))|^2+|w_t*delta_i|^2
+// is minimized by gauss-newton steps. in the normal equations every delta_i only couples to beta,
+// such that the delta_i are eliminated observation by observation and only a system of the size
+// of beta is solved
+use num_traits::{One, Zero};
+use container_traits::{AnyParameters, FromFn, IntoParameters, Iter, Len, LenNotEqualToRequiredLenError, LinearContainerConstructError as LCCE};
+
+use algebra_traits::Scalar;
+
+use algebra::VectorDyn;
+
+use matrix::MatrixDyn;
+use matrix_traits::{MatrixTryConstruct, MatrixView};
+use crate::dvec::{adjoint_mat_mul, adjoint_mat_vec, gram, lin_comb, mat_mul, mat_vec, norm2};
+use super::{from_dvec, into_dvec, jacobian_dvec, OptimizationError, OptimizationOptions};
+
+fn scale_rows<F:Scalar>(m:MatrixDyn<F>, weights:&VectorDyn<F::RealType>) -> MatrixDyn<F> {
+    MatrixDyn::from_fn((m.nrows(), m.ncols()),|(i,j)|m[(i,j)].clone()*F::from(weights[i].clone()))
+}
+
+fn scale_entries<F:Scalar>(v:&VectorDyn<F>, weights:&VectorDyn<F::RealType>) -> VectorDyn<F> {
+    VectorDyn::from_iter(v.iter().zip(weights.iter()).map(|(vi,wi)|vi.clone()*F::from(wi.clone())))
+}
+
+// returns the parameters beta and the corrected inputs. the weights of the inputs and outputs
+// are the inverse standard deviations of their components
+pub fn orthogonal_distance_regression<
+    F : Scalar,
+    B : Clone+AnyParameters<F,LCCE>,
+    T : Clone+AnyParameters<F,LCCE>,
+    Y : Clone+IntoParameters<F>>(
+        f              : impl Fn(B, T) -> Y,
+        beta0          : B,
+        observations   : Vec<(T, Y)>,
+        input_weights  : VectorDyn<F::RealType>,
+        output_weights : VectorDyn<F::RealType>,
+        opts           : Option<OptimizationOptions<F>>) -> Result<(B, Vec<T>), OptimizationError<F, B>> {
+    let opts=opts.unwrap_or_default();
+    let (inputs, targets):(Vec<T>, Vec<Y>)=observations.into_iter().unzip();
+    let inputs:Vec<VectorDyn<F>>=inputs.into_iter().map(into_dvec).collect();
+    let targets:Vec<VectorDyn<F>>=targets.into_iter().map(into_dvec).collect();
+    for (t, y) in inputs.iter().zip(targets.iter()) {
+        for (len, required) in [(t.len(), input_weights.len()), (y.len(), output_weights.len())] {
+            if len != required {
+                return Err(OptimizationError::ContainerConstruct(LenNotEqualToRequiredLenError::new(required, len).into()));
+            }
+        }
+    }
+    let input_weights2=VectorDyn::from_iter(input_weights.iter().map(|w|F::from(w.clone()*w.clone())));
+    let mut beta=into_dvec::<F,B>(beta0);
+    let mut corrected=inputs.clone();
+    let mut iter:u8=0;
+    while &iter < opts.max_iter() {
+        let p=beta.len();
+        let mut normal=MatrixDyn::from_fn((p,p),|_|F::zero());
+        let mut rhs=VectorDyn::from_iter((0..p).map(|_|F::zero()));
+        // the correction of delta_i is c_i-cm_i*dbeta
+        let mut eliminated=Vec::with_capacity(corrected.len());
+        for ((t, tc), y) in inputs.iter().zip(corrected.iter()).zip(targets.iter()) {
+            let fb=|b:VectorDyn<F>|into_dvec::<F,Y>(f(from_dvec(b), from_dvec(tc.clone())));
+            let ft=|t:VectorDyn<F>|into_dvec::<F,Y>(f(from_dvec(beta.clone()), from_dvec(t)));
+            let r=scale_entries(&lin_comb(F::one(), y, -F::one(), &fb(beta.clone())), &output_weights);
+            let a=scale_rows(jacobian_dvec(fb, beta.clone(), opts.fd().clone()), &output_weights);
+            let bm=scale_rows(jacobian_dvec(ft, tc.clone(), opts.fd().clone()), &output_weights);
+            let delta=lin_comb(F::one(), tc, -F::one(), t);
+            // (bm^H*bm+w_t^2)*[cm_i, c_i]=[bm^H*a, bm^H*r-w_t^2*delta]
+            let q=delta.len();
+            let bb=gram(&bm);
+            let m=MatrixDyn::from_fn((q,q),|(i,j)|if i == j { bb[(i,j)].clone()+input_weights2[i].clone() } else { bb[(i,j)].clone() });
+            let bta=adjoint_mat_mul(&bm, &a);
+            let btr=adjoint_mat_vec(&bm, &r);
+            let c_rhs=VectorDyn::from_iter((0..q).map(|i|btr[i].clone()-input_weights2[i].clone()*delta[i].clone()));
+            let cols=(0..p).map(|j|VectorDyn::from_iter((0..q).map(|i|bta[(i,j)].clone())))
+                           .chain(std::iter::once(c_rhs))
+                           .map(|col|crate::try_solve_least_squares(m.clone(), col))
+                           .collect::<Option<Vec<VectorDyn<F>>>>()
+                           .ok_or(OptimizationError::MatrixNotFullRank(m.clone(), from_dvec(beta.clone())))?;
+            let c=cols[p].clone();
+            let cm=MatrixDyn::try_from_cols(cols.into_iter().take(p).map(|col|col.into())).unwrap();
+            // a^H*(a-bm*cm)*dbeta=a^H*(r-bm*c)
+            let reduced=mat_mul(&bm, &cm);
+            let contribution=adjoint_mat_mul(&a, &MatrixDyn::from_fn((a.nrows(), p),|(i,j)|a[(i,j)].clone()-reduced[(i,j)].clone()));
+            normal=MatrixDyn::from_fn((p,p),|ij|normal[ij].clone()+contribution[ij].clone());
+            rhs=lin_comb(F::one(), &rhs, F::one(), &adjoint_mat_vec(&a, &lin_comb(F::one(), &r, -F::one(), &mat_vec(&bm, &c))));
+            eliminated.push((cm, c));
+        }
+        let dbeta=match crate::try_solve_least_squares(normal.clone(), rhs) {
+            Some(dbeta) => dbeta,
+            None => { return Err(OptimizationError::MatrixNotFullRank(normal, from_dvec(beta))); }
+        };
+        let mut step_norm2=norm2(&dbeta);
+        beta=lin_comb(F::one(), &beta, F::one(), &dbeta);
+        for (tc, (cm, c)) in corrected.iter_mut().zip(eliminated.iter()) {
+            let ddelta=lin_comb(F::one(), c, -F::one(), &mat_vec(cm, &dbeta));
+            step_norm2=step_norm2+norm2(&ddelta);
+            *tc=lin_comb(F::one(), tc, F::one(), &ddelta);
+        }
+        // the norm of the whole update of beta and the inputs
+        let tol=opts.target_cost().clone();
+        if step_norm2 < tol.clone()*tol {
+            return Ok((from_dvec(beta), corrected.into_iter().map(from_dvec).collect()));
+        }
+        iter+=1;
+    }
+    Err(OptimizationError::MaximalIteration(iter))
+}
+
+#[test]
+fn test_line_total_least_squares() {
+    use crate::OptimizationOptionsBuilder;
+    // for equal weights the orthogonal distance regression of a line is the principal axis
+    let ts=[0.0, 1.0, 2.0, 3.0, 4.0];
+    let et=[0.1, -0.05, 0.08, -0.1, 0.02];
+    let ey=[-0.1, 0.15, -0.05, 0.1, -0.12];
+    let points:Vec<(f64,f64)>=(0..5).map(|i|(ts[i]+et[i], 1.0+2.0*ts[i]+ey[i])).collect();
+    let line=|b:[f64;2], t:f64|b[0]+b[1]*t;
+    let opts=OptimizationOptionsBuilder::default()
+        .max_iter(30)
+        .build()
+        .unwrap();
+    let ones=VectorDyn::from(vec![1.0]);
+    let (b, corrected)=orthogonal_distance_regression(line, [0.5, 1.5], points.clone(), ones.clone(), ones, Some(opts)).unwrap();
+    let n=points.len() as f64;
+    let (xm, ym)=(points.iter().map(|p|p.0).sum::<f64>()/n, points.iter().map(|p|p.1).sum::<f64>()/n);
+    let sxx:f64=points.iter().map(|p|(p.0-xm)*(p.0-xm)).sum();
+    let syy:f64=points.iter().map(|p|(p.1-ym)*(p.1-ym)).sum();
+    let sxy:f64=points.iter().map(|p|(p.0-xm)*(p.1-ym)).sum();
+    let slope=(syy-sxx+((syy-sxx)*(syy-sxx)+4.0*sxy*sxy).sqrt())/(2.0*sxy);
+    assert!((b[1] - slope).abs() < 1e-8);
+    assert!((b[0] - (ym-slope*xm)).abs() < 1e-8);
+    // the corrected points are the orthogonal projections onto the line
+    for ((t, y), tc) in points.iter().zip(corrected.iter()) {
+        assert!(((tc - t) + b[1]*(line(b, *tc) - y)).abs() < 1e-8);
+    }
+}
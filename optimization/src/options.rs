@@ -1,10 +1,236 @@
+use num_traits::Zero;
 use super::FiniteDifference;
 use algebra_traits::{Scalar, CastFromf64};
+
+// damping parameter is multiplied by increase if a step is rejected
+// and by decrease if a step is accepted, it is kept within [min_damping, max_damping].
+// with marquardt_scaling the damping of every parameter is scaled by the largest norm of its
+// column of the weighted jacobian seen so far, which makes the steps independent of the units
+// of the parameters
+#[derive(Clone, Copy, Debug, derive_getters::Getters)]
+pub struct LevenbergMarquardtOptions<R> {
+    initial_damping: R,
+    increase: R,
+    decrease: R,
+    min_damping: R,
+    max_damping: R,
+    marquardt_scaling: bool,
+}
+
+impl<R> LevenbergMarquardtOptions<R> {
+    pub fn new(initial_damping: R, increase: R, decrease: R, min_damping: R, max_damping: R, marquardt_scaling: bool) -> Self {
+        Self { initial_damping, increase, decrease, min_damping, max_damping, marquardt_scaling }
+    }
+}
+
+impl<R:CastFromf64> Default for LevenbergMarquardtOptions<R> {
+    fn default() -> Self {
+        Self {
+            initial_damping: R::from_f64(1e-3),
+            increase: R::from_f64(10.0),
+            decrease: R::from_f64(0.1),
+            min_damping: R::from_f64(1e-12),
+            max_damping: R::from_f64(1e12),
+            marquardt_scaling: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub enum Method<R> {
+    #[default]
+    GaussNewton,
+    LevenbergMarquardt(LevenbergMarquardtOptions<R>),
+}
+
+// backtracking until the armijo condition
+// cost(x+t*update) <= cost(x) + c1*t*slope
+// holds, where t is multiplied by shrink in every trial
+#[derive(Clone, Copy, Debug, derive_getters::Getters)]
+pub struct LineSearchOptions<R> {
+    c1: R,
+    shrink: R,
+    min_step: R,
+}
+
+impl<R> LineSearchOptions<R> {
+    pub fn new(c1: R, shrink: R, min_step: R) -> Self {
+        Self { c1, shrink, min_step }
+    }
+}
+
+impl<R:CastFromf64> Default for LineSearchOptions<R> {
+    fn default() -> Self {
+        Self {
+            c1: R::from_f64(1e-4),
+            shrink: R::from_f64(0.5),
+            min_step: R::from_f64(1e-10),
+        }
+    }
+}
+
+// dogleg steps are accepted if the ratio of actual to predicted
+// decrease of the cost is larger than eta
+#[derive(Clone, Copy, Debug, derive_getters::Getters)]
+pub struct TrustRegionOptions<R> {
+    initial_radius: R,
+    max_radius: R,
+    eta: R,
+}
+
+impl<R> TrustRegionOptions<R> {
+    pub fn new(initial_radius: R, max_radius: R, eta: R) -> Self {
+        Self { initial_radius, max_radius, eta }
+    }
+}
+
+impl<R:CastFromf64> Default for TrustRegionOptions<R> {
+    fn default() -> Self {
+        Self {
+            initial_radius: R::from_f64(1.0),
+            max_radius: R::from_f64(1e3),
+            eta: R::from_f64(1e-3),
+        }
+    }
+}
+
+// how the gauss-newton update is turned into a step,
+// levenberg-marquardt controls its step by the damping.
+// the default line search only accepts steps which decrease the cost,
+// FullStep takes the whole update without checking the cost
+#[derive(Clone, Copy, Debug)]
+pub enum StepControl<R> {
+    FullStep,
+    LineSearch(LineSearchOptions<R>),
+    TrustRegion(TrustRegionOptions<R>),
+}
+
+impl<R:CastFromf64> Default for StepControl<R> {
+    fn default() -> Self {
+        StepControl::LineSearch(LineSearchOptions::default())
+    }
+}
+
+// the limited memory variant stores the given number of pairs of steps and gradient changes
+#[derive(Clone, Copy, Debug, Default)]
+pub enum QuasiNewtonMethod {
+    #[default]
+    Bfgs,
+    LimitedMemoryBfgs(usize),
+}
+
+// step lengths t satisfying the strong wolfe conditions
+// f(x+t*p) <= f(x) + c1*t*g(x).p and |g(x+t*p).p| <= c2*|g(x).p|
+// are searched with at most max_evaluations evaluations of f
+#[derive(Clone, Copy, Debug, derive_getters::Getters)]
+pub struct WolfeOptions<R> {
+    c1: R,
+    c2: R,
+    max_evaluations: u8,
+}
+
+impl<R> WolfeOptions<R> {
+    pub fn new(c1: R, c2: R, max_evaluations: u8) -> Self {
+        Self { c1, c2, max_evaluations }
+    }
+}
+
+impl<R:CastFromf64> Default for WolfeOptions<R> {
+    fn default() -> Self {
+        Self {
+            c1: R::from_f64(1e-4),
+            c2: R::from_f64(0.9),
+            max_evaluations: 30,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub enum BroydenVariant {
+    // rank one updates of the jacobian
+    #[default]
+    Good,
+    // rank one updates of the pseudo inverse of the jacobian
+    Bad,
+}
+
+// the jacobian is recomputed if a step does not decrease
+// the cost at least by the factor stall
+#[derive(Clone, Copy, Debug, derive_getters::Getters)]
+pub struct BroydenOptions<R> {
+    variant: BroydenVariant,
+    stall: R,
+}
+
+impl<R> BroydenOptions<R> {
+    pub fn new(variant: BroydenVariant, stall: R) -> Self {
+        Self { variant, stall }
+    }
+}
+
+impl<R:CastFromf64> Default for BroydenOptions<R> {
+    fn default() -> Self {
+        Self {
+            variant: BroydenVariant::default(),
+            stall: R::from_f64(0.9),
+        }
+    }
+}
+
+// how the jacobian is obtained in the iterations, broyden computes it
+// only at the start and if the secant updates stall
+#[derive(Clone, Copy, Debug, Default)]
+pub enum JacobianUpdate<R> {
+    #[default]
+    Exact,
+    Broyden(BroydenOptions<R>),
+}
+
+#[derive(Clone, Copy, Debug, derive_builder::Builder, derive_getters::Getters)]
+#[builder(default)]
+pub struct QuasiNewtonOptions<F:Scalar> {
+    fd: FiniteDifference<F>,
+    gradient_tolerance: F::RealType,
+    step_tolerance: F::RealType,
+    max_iter: u8,
+    method: QuasiNewtonMethod,
+    wolfe: WolfeOptions<F::RealType>,
+}
+
+impl<F:Scalar> Default for QuasiNewtonOptions<F> {
+    fn default() -> Self {
+        Self {
+            fd: FiniteDifference::<F>::default(),
+            gradient_tolerance: F::RealType::from_f64(1e-6),
+            step_tolerance: F::RealType::from_f64(1e-12),
+            max_iter: 100 as u8,
+            method: QuasiNewtonMethod::default(),
+            wolfe: WolfeOptions::default(),
+        }
+    }
+}
+
+// the solvers stop if the norm of the update is smaller than target_cost,
+// if the cost is smaller than cost_tolerance or if the norm of the gradient
+// is smaller than gradient_tolerance. the latter two are disabled by default.
+// the solvers with constraints require in addition that the norm of the constraint violation
+// is smaller than constraint_tolerance, they stop as well if moreover the gradient of the
+// lagrangian is smaller than stationarity_tolerance and, for inequalities, the mean
+// complementarity of slacks and multipliers is smaller than complementarity_tolerance
 #[derive(Clone, Copy, Debug, derive_builder::Builder, derive_getters::Getters)]
+#[builder(default)]
 pub struct OptimizationOptions<F:Scalar> {
     fd: FiniteDifference<F>,
     target_cost: F::RealType,
+    cost_tolerance: F::RealType,
+    gradient_tolerance: F::RealType,
+    constraint_tolerance: F::RealType,
+    stationarity_tolerance: F::RealType,
+    complementarity_tolerance: F::RealType,
     max_iter: u8,
+    method: Method<F::RealType>,
+    step_control: StepControl<F::RealType>,
+    jacobian_update: JacobianUpdate<F::RealType>,
 }
 
 impl<F:Scalar> Default for OptimizationOptions<F> {
@@ -12,7 +238,15 @@ impl<F:Scalar> Default for OptimizationOptions<F> {
         Self {
             fd: FiniteDifference::<F>::default(),
             target_cost: F::RealType::from_f64(1e-10),
+            cost_tolerance: F::RealType::zero(),
+            gradient_tolerance: F::RealType::zero(),
+            constraint_tolerance: F::RealType::from_f64(1e-10),
+            stationarity_tolerance: F::RealType::from_f64(1e-6),
+            complementarity_tolerance: F::RealType::from_f64(1e-10),
             max_iter: 10 as u8,
+            method: Method::default(),
+            step_control: StepControl::default(),
+            jacobian_update: JacobianUpdate::default(),
         }
     }
-}
\ No newline at end of file
+}
@@ -1,16 +1,34 @@
+pub mod autodiff;
+pub mod bounded;
+pub mod broyden;
+pub mod constrained;
+pub mod interior_point;
+pub mod irls;
+pub mod levenberg_marquardt;
+pub mod line_search;
+pub mod manifold;
+pub mod sensitivity;
+pub mod sparsity;
+pub mod trust_region;
+pub mod uncertainties;
+pub mod whitening;
 
 use num_traits::One;
-use container_traits::{FromElement, AnyFromParameters, AnyParameters, Concat, Concatenated, IntoParameters, LinearContainerConstructError as LCCE};
+use container_traits::{FromElement, AnyFromParameters, AnyParameters, Concat, Concatenated, IntoParameters, Len, LinearContainerConstructError as LCCE};
 
 use algebra_traits::{Norm, Scalar, ScalarMul, TryAdd, TrySub};
 
 use algebra::VectorDyn;
 
 use matrix::MatrixDyn;
-use matrix_traits::{Matrix, MatrixTryConstruct};
-use super::{from_dvec, into_dvec, OptimizationError, OptimizationOptions};
+use matrix_traits::{Matrix, MatrixTryConstruct, MatrixView};
+use super::{from_dvec, into_dvec, Bounds, JacobianUpdate, Method, Observer, OptimizationError, OptimizationOptions, RobustLoss, SolveReport, StepControl, TerminationReason};
+use super::dvec::{adjoint_mat_vec, lin_comb};
+use super::report::{Evaluations, History};
+use whitening::Whitening;
 
 #[derive(Clone, Debug, derive_builder::Builder)]
+#[builder(build_fn(validate="Self::validate"))]
 pub struct Problem<F:Scalar,
                    X,
                    Y,
@@ -26,7 +44,20 @@ pub struct Problem<F:Scalar,
     weights: VectorDyn<F::RealType>,
 
     #[builder(default)]
-    options:OptimizationOptions<F>
+    options:OptimizationOptions<F>,
+
+    #[builder(setter(strip_option), default)]
+    robust_loss:Option<RobustLoss<F::RealType>>,
+
+    #[builder(setter(strip_option), default)]
+    bounds:Option<Bounds<F::RealType>>,
+
+    #[builder(setter(strip_option), default)]
+    observer:Option<Observer<F::RealType,X>>,
+
+    // correlated noise of the residuals, applied before the weights
+    #[builder(setter(strip_option), default)]
+    whitening:Option<Whitening<F>>
 }
 
 impl<F    : Scalar,
@@ -44,7 +75,11 @@ impl<F    : Scalar,
                 first_guess: Some(x),
                 target: None,
                 weights: None,
-                options:Some(OptimizationOptions::default())
+                options:Some(OptimizationOptions::default()),
+                robust_loss: None,
+                bounds: None,
+                observer: None,
+                whitening: None
             }
         }
 
@@ -61,6 +96,20 @@ impl<F    : Scalar,
             self
         }
     }
+impl<F    : Scalar,
+     X,
+     Y,
+     Func : Fn(X) -> Y> ProblemBuilder<F,X,Y,Func> {
+        // the whitening acts on the residuals, whose number is the one of the weights
+        fn validate(&self) -> Result<(), String> {
+            match (&self.whitening, &self.weights) {
+                (Some(Some(whitening)), Some(weights)) if whitening.matrix().nrows() != weights.len() =>
+                    Err(format!("Whitening of dimension {} does not match the {} residuals", whitening.matrix().nrows(), weights.len())),
+                _ => Ok(()),
+            }
+        }
+    }
+
 // pub struct ProblemBuilder<X,Y> {
 //     function: Option<Box<dyn Fn(&X) -> Y>>,
 //     first_guess: Option<X>,
@@ -118,7 +167,61 @@ impl<F    : Scalar,
                 first_guess,
                 target,
                 weights,
-                options:self.options.clone()}
+                options:self.options.clone(),
+                robust_loss:self.robust_loss.clone(),
+                bounds:self.bounds.clone(),
+                observer:self.observer.clone(),
+                whitening:self.whitening.as_ref().map(|w|w.prepend_identity(ndofs))}
+    }
+}
+
+// the parts which do not need the global parameters of X
+impl<F    : Scalar,
+     X    : Clone,
+     Y    : IntoParameters<F>+Clone,
+     Func : Fn(X) -> Y> Problem<F,X,Y,Func> {
+    fn eval(&self, x:X, evaluations:&Evaluations) -> Y {
+        evaluations.count_function();
+        (self.function)(x)
+    }
+
+    fn weighted_jacobian(&self, jac:MatrixDyn<F>) -> MatrixDyn<F> {
+        let jac=match &self.whitening {
+            Some(whitening) => whitening.whiten_jacobian(jac),
+            None            => jac,
+        };
+        MatrixDyn::try_from_rows(
+            jac.into_rows()
+               .zip(self.weights.clone().into_iter())
+               .map(|(r,wi)|r.scalar_mul(&F::from(wi)))).unwrap()
+    }
+
+    // weighted difference between target and function value
+    fn weighted_residual(&self, x:X, evaluations:&Evaluations) -> Result<VectorDyn<F>, OptimizationError<F,X>> {
+        self.weighted_difference(self.eval(x, evaluations))
+    }
+
+    fn weighted_difference<EX>(&self, fx:Y) -> Result<VectorDyn<F>, OptimizationError<F,X,EX>> {
+        let y_dvec:VectorDyn<F>=into_dvec(self.target.clone());
+        let fx:VectorDyn<F>=into_dvec(fx);
+        let res:VectorDyn<F>=y_dvec.clone().try_sub(fx.clone())
+            .map_err(|_|OptimizationError::<F,X,EX>::Difference(y_dvec,fx))?;
+        let res=match &self.whitening {
+            Some(whitening) => whitening.whiten(&res),
+            None            => res,
+        };
+        Ok(container_traits::vec_op::try_binary_operation(res.into(),self.weights.clone().into(),|(r,w)|r*w).unwrap().into())
+    }
+
+    // termination tests which do not need the update
+    fn converged(&self, wjac:&MatrixDyn<F>, wres:&VectorDyn<F>) -> Option<TerminationReason> {
+        if &cost(wres) < self.options.cost_tolerance() {
+            Some(TerminationReason::CostTolerance)
+        } else if &adjoint_mat_vec(wjac, wres).norm() < self.options.gradient_tolerance() {
+            Some(TerminationReason::GradientTolerance)
+        } else {
+            None
+        }
     }
 }
 
@@ -126,51 +229,127 @@ impl<F    : Scalar,
      X    : AnyParameters<F,LCCE>+Clone,
      Y    : IntoParameters<F>+Clone,
      Func : Fn(X) -> Y> Problem<F,X,Y,Func> {
-    fn numerical_derivative(&self, x:X) -> MatrixDyn<F>
+    fn numerical_derivative(&self, x:X, evaluations:&Evaluations) -> MatrixDyn<F>
     {
-        super::jacobian(&self.function, x, self.options.fd().clone())
+        super::jacobian(|x:X|self.eval(x, evaluations), x, self.options.fd().clone())
+    }
+
+    fn try_update(x:X, update:VectorDyn<F>) -> Result<X, OptimizationError<F,X>> {
+        let lhs=into_dvec(x);
+        if lhs.is_addable_by(&update).is_ok() {
+            Ok(from_dvec(lhs.try_add(update).ok().unwrap()))
+        } else {
+            Err(OptimizationError::<F,X>::Sum(lhs,update))
+        }
+    }
+
+    fn distance(x:&X, xnew:&X) -> F::RealType {
+        lin_comb(F::one(), &into_dvec(xnew.clone()), -F::one(), &into_dvec(x.clone())).norm()
+                                                                                      .into_signed()
+    }
+
+    // reaching the maximal number of iterations is an error if only the solution is requested
+    fn solution(&self, report:SolveReport<F::RealType,X>) -> Result<X, OptimizationError<F,X>> {
+        match report.termination() {
+            TerminationReason::MaxIterations => Err(OptimizationError::MaximalIteration(*self.options.max_iter())),
+            _                                => Ok(report.into_solution()),
+        }
     }
 
     pub fn solve(&self) -> Result<X, OptimizationError<F,X, LCCE>> {
-        self.solve_with_der(|x:X| self.numerical_derivative(x))
+        self.solve_report()
+            .and_then(|report|self.solution(report))
     }
 
     pub fn solve_with_der(&self, derivative:impl Fn(X) -> MatrixDyn<F>) -> Result<X, OptimizationError<F,X>> {
-        let f=&self.function;
+        self.solve_with_der_report(derivative)
+            .and_then(|report|self.solution(report))
+    }
+
+    pub fn solve_report(&self) -> Result<SolveReport<F::RealType,X>, OptimizationError<F,X>> {
+        let evaluations=Evaluations::default();
+        self.solve_counted(|x:X| self.numerical_derivative(x, &evaluations), &evaluations)
+    }
+
+    pub fn solve_with_der_report(&self, derivative:impl Fn(X) -> MatrixDyn<F>) -> Result<SolveReport<F::RealType,X>, OptimizationError<F,X>> {
+        self.solve_counted(derivative, &Evaluations::default())
+    }
+
+    // the evaluations of the function by the derivative are counted if it is given the same counters
+    fn solve_counted(&self,
+                     derivative:impl Fn(X) -> MatrixDyn<F>,
+                     evaluations:&Evaluations) -> Result<SolveReport<F::RealType,X>, OptimizationError<F,X>> {
+        let derivative=|x:X|{
+            evaluations.count_jacobian();
+            derivative(x)
+        };
+        match &self.robust_loss {
+            Some(loss) => self.irls(derivative, loss, evaluations).map(|(report,_)|report),
+            None       => self.solve_least_squares(derivative, evaluations),
+        }
+    }
+
+    fn solve_least_squares(&self,
+                           derivative:impl Fn(X) -> MatrixDyn<F>,
+                           evaluations:&Evaluations) -> Result<SolveReport<F::RealType,X>, OptimizationError<F,X>> {
+        if let Some(bounds) = &self.bounds {
+            return self.projected_gauss_newton(derivative, bounds, evaluations);
+        }
+        if let JacobianUpdate::Broyden(broyden) = self.options.jacobian_update() {
+            return self.broyden(derivative, broyden.clone(), evaluations);
+        }
+        match (self.options.method().clone(), self.options.step_control().clone()) {
+            (Method::LevenbergMarquardt(lm), _                          ) => self.levenberg_marquardt(derivative, lm, evaluations),
+            (Method::GaussNewton,            StepControl::TrustRegion(tr)) => self.dogleg(derivative, tr, evaluations),
+            (Method::GaussNewton,            _                          ) => self.gauss_newton(derivative, evaluations),
+        }
+    }
+
+    fn gauss_newton(&self,
+                    derivative:impl Fn(X) -> MatrixDyn<F>,
+                    evaluations:&Evaluations) -> Result<SolveReport<F::RealType,X>, OptimizationError<F,X>> {
         let opts: OptimizationOptions<F> = self.options.clone();
         let mut x=self.first_guess.clone();
-        let y_dvec:VectorDyn<F>=into_dvec(self.target.clone());
-        let weights=||self.weights.clone();
+        let mut history=History::new(self.observer.as_ref());
         let mut iter:u8=0;
         while &iter < opts.max_iter() {
-            let jac=derivative(x.clone());
-            let wjac=MatrixDyn::try_from_rows(
-                    jac.into_rows()
-                       .zip(weights().into_iter())
-                       .map(|(r,wi)|r.scalar_mul(&F::from(wi)))).unwrap();
-            let res:VectorDyn<F>=y_dvec.clone().try_sub(into_dvec(f(x.clone())))
-                .map_err(|_|OptimizationError::<F,X>::Difference(y_dvec.clone(),into_dvec(f(x.clone()))))?;
-            let wres=container_traits::vec_op::try_binary_operation(res.into(),weights().into(),|(r,w)|r*w).unwrap().into();
-            let update=match super::try_solve_least_squares(wjac.clone(),wres) {
+            let wjac=self.weighted_jacobian(derivative(x.clone()));
+            let wres=self.weighted_residual(x.clone(), evaluations)?;
+            if let Some(reason) = self.converged(&wjac, &wres) {
+                return Ok(history.finish(x, reason, evaluations));
+            }
+            let update=match super::try_solve_least_squares(wjac.clone(),wres.clone()) {
                 Some(update) => update,
                 None => { return Err(OptimizationError::MatrixNotFullRank(wjac, x)); }
             };
-            if &updatenorm() < opts.target_cost() { break; }
-            let lhs=into_dvec(x);
-            if lhs.is_addable_by(&update).is_ok() {
-                x=from_dvec(lhs.try_add(update).ok().unwrap());
-            } else {
-                return Err(OptimizationError::<F,X>::Sum(lhs,update));
+            let update_norm=update.norm().into_signed();
+            if &update_norm < opts.target_cost() {
+                return Ok(history.finish(x, TerminationReason::StepTolerance, evaluations));
             }
+            let (xnew, step_norm)=match opts.step_control() {
+                StepControl::LineSearch(ls) => match self.backtracking(x.clone(), &wjac, &wres, update, ls.clone(), evaluations)? {
+                    Some(xnew) => {
+                        let step_norm=Self::distance(&x, &xnew);
+                        (xnew, step_norm)
+                    },
+                    // no decrease along the update possible
+                    None => { return Ok(history.finish(x, TerminationReason::StepTolerance, evaluations)); }
+                },
+                _ => (Self::try_update(x, update)?, update_norm)
+            };
+            x=xnew;
+            history.record(&x, cost(&wres), step_norm);
             iter+=1;
         }
-        if &iter == opts.max_iter() {
-            return Err(OptimizationError::MaximalIteration(iter));
-        }
-        Ok(x)
+        Ok(history.finish(x, TerminationReason::MaxIterations, evaluations))
     }
 }
 
+// sum of squares of the weighted residual
+fn cost<F:Scalar>(wres:&VectorDyn<F>) -> F::RealType {
+    super::dvec::norm2(wres)
+}
+
 
 // #[derive(Clone)]
 // pub struct ProblemInclDerivative<F:Scalar,
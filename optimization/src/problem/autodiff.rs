@@ -0,0 +1,80 @@
+// if the function of the problem is generic in the scalar, the jacobian can be computed
+// exactly by evaluating the function with dual or complex numbers instead of finite differences
+use container_traits::{AnyFromParameters, AnyParameters, IntoParameters, LinearContainerConstructError as LCCE};
+
+use algebra_traits::{NonZero, RealNumber};
+
+use algebra::{Complex, Dual};
+
+use crate::{jacobian_ad, jacobian_complex_step, OptimizationError, SolveReport};
+use super::Problem;
+
+impl<F    : RealNumber,
+     X    : AnyParameters<F,LCCE>+Clone,
+     Y    : IntoParameters<F>+Clone,
+     Func : Fn(X) -> Y> Problem<F,X,Y,Func> {
+
+    // f_dual is the function of the problem instantiated with dual numbers,
+    // e.g. model::<Dual<f64>> if the problem was built with model::<f64>
+    pub fn solve_ad<XD : AnyFromParameters<Dual<F>,LCCE>,
+                    YD : IntoParameters<Dual<F>>>(&self, f_dual:impl Fn(XD) -> YD) -> Result<X, OptimizationError<F,X>> {
+        self.solve_with_der(|x:X|jacobian_ad(&f_dual, x))
+    }
+
+    pub fn solve_ad_report<XD : AnyFromParameters<Dual<F>,LCCE>,
+                           YD : IntoParameters<Dual<F>>>(&self, f_dual:impl Fn(XD) -> YD) -> Result<SolveReport<F,X>, OptimizationError<F,X>> {
+        self.solve_with_der_report(|x:X|jacobian_ad(&f_dual, x))
+    }
+
+    // f_complex is the function of the problem instantiated with complex numbers,
+    // it has to be real analytic, see jacobian_complex_step
+    pub fn solve_complex_step<XC : AnyFromParameters<Complex<F>,LCCE>,
+                              YC : IntoParameters<Complex<F>>>(&self, f_complex:impl Fn(XC) -> YC, step:NonZero<F>) -> Result<X, OptimizationError<F,X>> {
+        self.solve_with_der(|x:X|jacobian_complex_step(&f_complex, x, step.clone()))
+    }
+}
+
+#[cfg(test)]
+const DECAY_TIMES:[f64;5]=[0.0, 0.5, 1.0, 2.0, 4.0];
+
+#[cfg(test)]
+fn decay<S:algebra_traits::Scalar>(p:[S;2]) -> Vec<S> {
+    use algebra_traits::{CastFromf64, Exp};
+    let [a, k]=p;
+    DECAY_TIMES.iter()
+               .map(|t|a.clone()*(-k.clone()*S::from(S::RealType::from_f64(*t))).exp())
+               .collect()
+}
+
+#[test]
+fn test_decay_fit_ad() {
+    use crate::{OptimizationOptionsBuilder, ProblemBuilder};
+    let opts = OptimizationOptionsBuilder::default()
+        .max_iter(30)
+        .build()
+        .unwrap();
+    let problem = ProblemBuilder::<f64,_,_,_>::new(decay::<f64>, [1.0, 1.0])
+        .target(decay([2.5, 0.3]))
+        .set_weights_to_one()
+        .options(opts)
+        .build().unwrap();
+    let report = problem.solve_ad_report(decay::<Dual<f64>>).unwrap();
+    let p = report.solution();
+    assert!((p[0] - 2.5).abs() < 1e-10);
+    assert!((p[1] - 0.3).abs() < 1e-10);
+    // the exact jacobian does not need evaluations of the function
+    let numerical = problem.solve_report().unwrap();
+    assert!(report.function_evaluations() < numerical.function_evaluations());
+}
+
+#[test]
+fn test_decay_fit_complex_step() {
+    use crate::ProblemBuilder;
+    let problem = ProblemBuilder::<f64,_,_,_>::new(decay::<f64>, [2.0, 0.2])
+        .target(decay([2.5, 0.3]))
+        .set_weights_to_one()
+        .build().unwrap();
+    let p = problem.solve_complex_step(decay::<Complex<f64>>, NonZero::try_new(1e-20).unwrap()).unwrap();
+    assert!((p[0] - 2.5).abs() < 1e-10);
+    assert!((p[1] - 0.3).abs() < 1e-10);
+}
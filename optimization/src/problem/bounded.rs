@@ -0,0 +1,112 @@
+use num_traits::{One, Zero};
+use container_traits::{AnyParameters, IntoParameters, Iter, Len, LenNotEqualToRequiredLenError, LinearContainerConstructError as LCCE};
+
+use algebra_traits::{Norm, Scalar, TryIntoReal};
+
+use algebra::VectorDyn;
+
+use matrix::MatrixDyn;
+use matrix_traits::{Matrix, MatrixTryConstruct};
+use crate::dvec::lin_comb;
+use crate::{from_dvec, into_dvec, Bounds, LineSearchOptions, OptimizationError, SolveReport, StepControl, TerminationReason};
+use crate::report::{Evaluations, History};
+use super::{cost, Problem};
+
+impl<F    : Scalar,
+     X    : AnyParameters<F,LCCE>+Clone,
+     Y    : IntoParameters<F>+Clone,
+     Func : Fn(X) -> Y> Problem<F,X,Y,Func> {
+
+    // parameters which are at a bound and which the gauss-newton update pushes outwards are kept fixed,
+    // the step to the projection of x+t*update onto the box is accepted if it decreases the cost
+    pub(super) fn projected_gauss_newton(&self,
+                                         derivative:impl Fn(X) -> MatrixDyn<F>,
+                                         bounds:&Bounds<F::RealType>,
+                                         evaluations:&Evaluations) -> Result<SolveReport<F::RealType,X>, OptimizationError<F,X>> {
+        let opts=&self.options;
+        let n=into_dvec::<F,X>(self.first_guess.clone()).len();
+        if bounds.len() != n {
+            return Err(OptimizationError::ContainerConstruct(LenNotEqualToRequiredLenError::new(n, bounds.len()).into()));
+        }
+        bounds.try_check()
+              .map_err(OptimizationError::InconsistentBounds)?;
+        if !bounds.contains(&into_dvec::<F,X>(self.first_guess.clone())) {
+            return Err(OptimizationError::InfeasibleFirstGuess(self.first_guess.clone()));
+        }
+        let ls=match opts.step_control() {
+            StepControl::LineSearch(ls) => ls.clone(),
+            _ => LineSearchOptions::default(),
+        };
+        let re=|f:&F|f.clone().try_into_real().unwrap();
+        let mut x=self.first_guess.clone();
+        let mut history=History::new(self.observer.as_ref());
+        let mut iter:u8=0;
+        while &iter < opts.max_iter() {
+            let wjac=self.weighted_jacobian(derivative(x.clone()));
+            let wres=self.weighted_residual(x.clone(), evaluations)?;
+            if let Some(reason) = self.converged(&wjac, &wres) {
+                return Ok(history.finish(x, reason, evaluations));
+            }
+            let update=match crate::try_solve_least_squares(wjac.clone(),wres.clone()) {
+                Some(update) => update,
+                None => { return Err(OptimizationError::MatrixNotFullRank(wjac, x)); }
+            };
+            let xd:VectorDyn<F>=into_dvec(x.clone());
+            let free:Vec<bool>=
+                xd.iter()
+                  .zip(update.iter())
+                  .enumerate()
+                  .map(|(i,(xi,di))|!((&re(xi) <= bounds.lower(i) && re(di) < F::RealType::zero())
+                                   || (&re(xi) >= bounds.upper(i) && re(di) > F::RealType::zero())))
+                  .collect();
+            // the projected gradient vanishes
+            if !free.iter().any(|f|*f) {
+                return Ok(history.finish(x, TerminationReason::GradientTolerance, evaluations));
+            }
+            let update=if free.iter().all(|f|*f) {
+                update
+            } else {
+                let cols:Vec<_>=
+                    wjac.clone()
+                        .into_cols()
+                        .zip(free.iter())
+                        .filter(|(_,f)|**f)
+                        .map(|(c,_)|c)
+                        .collect();
+                let reduced=MatrixDyn::try_from_cols(cols.into_iter()).unwrap();
+                let reduced_update=match crate::try_solve_least_squares(reduced.clone(),wres.clone()) {
+                    Some(update) => update,
+                    None => { return Err(OptimizationError::MatrixNotFullRank(reduced, x)); }
+                };
+                let mut iter=reduced_update.into_iter();
+                VectorDyn::from_iter(free.iter().map(|f|if *f { iter.next().unwrap() } else { F::zero() }))
+            };
+            let projected=|t:F::RealType|bounds.project(lin_comb(F::one(), &xd, F::from(t), &update));
+            let step=lin_comb(F::one(), &projected(F::RealType::one()), -F::one(), &xd);
+            if &step.norm() < opts.target_cost() {
+                return Ok(history.finish(x, TerminationReason::StepTolerance, evaluations));
+            }
+            let c0=cost(&wres);
+            let mut t=F::RealType::one();
+            let mut accepted=None;
+            while &t >= ls.min_step() {
+                let xt:X=from_dvec(projected(t.clone()));
+                if cost(&self.weighted_residual(xt.clone(), evaluations)?) < c0 {
+                    accepted=Some(xt);
+                    break;
+                }
+                t=t*ls.shrink().clone();
+            }
+            match accepted {
+                Some(xt) => {
+                    let step_norm=Self::distance(&x, &xt);
+                    x=xt;
+                    history.record(&x, c0, step_norm);
+                },
+                None => { return Ok(history.finish(x, TerminationReason::StepTolerance, evaluations)); }
+            }
+            iter+=1;
+        }
+        Ok(history.finish(x, TerminationReason::MaxIterations, evaluations))
+    }
+}
@@ -0,0 +1,153 @@
+// broyden's method is the gauss-newton iteration with a jacobian which is computed once and afterwards
+// changed by rank one updates, such that it maps the last step onto the observed change of the
+// weighted function. the bad variant updates the pseudo inverse instead of the jacobian,
+// then the gradient test uses the last computed jacobian
+use num_traits::{One, Zero};
+use container_traits::{AnyParameters, FromFn, IntoParameters, LinearContainerConstructError as LCCE};
+
+use algebra_traits::{Conjugate, Norm, Scalar, TryDiv};
+
+use algebra::VectorDyn;
+
+use matrix::MatrixDyn;
+use matrix_traits::{MatrixTryConstruct, MatrixView};
+use crate::dvec::{dot, lin_comb, mat_vec, scale};
+use crate::{BroydenOptions, BroydenVariant, OptimizationError, SolveReport, TerminationReason};
+use crate::report::{Evaluations, History};
+use super::{cost, Problem};
+
+// the update of m with the smallest frobenius norm such that m*b=a,
+// m is unchanged if b vanishes
+fn secant_update<F:Scalar>(m:MatrixDyn<F>, a:&VectorDyn<F>, b:&VectorDyn<F>) -> MatrixDyn<F> {
+    let u=lin_comb(F::one(), a, -F::one(), &mat_vec(&m, b));
+    match F::one().try_div(dot(b, b)) {
+        Ok(c) => {
+            let u=scale(c, &u);
+            MatrixDyn::from_fn((m.nrows(), m.ncols()),|(i,j)|m[(i,j)].clone()+u[i].clone()*b[j].conjugate())
+        },
+        Err(_) => m,
+    }
+}
+
+// the columns are the least squares solutions for the unit vectors
+fn pseudo_inverse<F:Scalar>(m:&MatrixDyn<F>) -> Option<MatrixDyn<F>> {
+    let nrows=m.nrows();
+    let unit=|k:usize|VectorDyn::from_iter((0..nrows).map(|i|if i == k { F::one() } else { F::zero() }));
+    let cols:Option<Vec<VectorDyn<F>>>=(0..nrows).map(|k|crate::try_solve_least_squares(m.clone(), unit(k)))
+                                                 .collect();
+    cols.map(|cols|MatrixDyn::try_from_cols(cols.into_iter().map(|c|c.into())).unwrap())
+}
+
+impl<F    : Scalar,
+     X    : AnyParameters<F,LCCE>+Clone,
+     Y    : IntoParameters<F>+Clone,
+     Func : Fn(X) -> Y> Problem<F,X,Y,Func> {
+
+    pub(super) fn broyden(&self,
+                          derivative:impl Fn(X) -> MatrixDyn<F>,
+                          broyden:BroydenOptions<F::RealType>,
+                          evaluations:&Evaluations) -> Result<SolveReport<F::RealType,X>, OptimizationError<F,X>> {
+        let opts=&self.options;
+        let bad=matches!(broyden.variant(), BroydenVariant::Bad);
+        let refresh=|x:&X|{
+            let wjac=self.weighted_jacobian(derivative(x.clone()));
+            let inverse=if bad { pseudo_inverse(&wjac) } else { None };
+            (wjac, inverse)
+        };
+        let mut x=self.first_guess.clone();
+        let mut wres=self.weighted_residual(x.clone(), evaluations)?;
+        let (mut wjac, mut inverse)=refresh(&x);
+        // the jacobian has been computed at x
+        let mut fresh=true;
+        let mut history=History::new(self.observer.as_ref());
+        let mut iter:u8=0;
+        while &iter < opts.max_iter() {
+            if let Some(reason) = self.converged(&wjac, &wres) {
+                return Ok(history.finish(x, reason, evaluations));
+            }
+            let update=if bad {
+                inverse.as_ref().map(|inverse|mat_vec(inverse, &wres))
+            } else {
+                crate::try_solve_least_squares(wjac.clone(), wres.clone())
+            };
+            let update=match update {
+                Some(update) => update,
+                None if fresh => { return Err(OptimizationError::MatrixNotFullRank(wjac, x)); },
+                None => {
+                    (wjac, inverse)=refresh(&x);
+                    fresh=true;
+                    iter+=1;
+                    continue;
+                },
+            };
+            let update_norm=update.norm().into_signed();
+            if &update_norm < opts.target_cost() {
+                return Ok(history.finish(x, TerminationReason::StepTolerance, evaluations));
+            }
+            let xnew=Self::try_update(x.clone(), update.clone())?;
+            let wres_new=self.weighted_residual(xnew.clone(), evaluations)?;
+            let c0=cost(&wres);
+            if !fresh && cost(&wres_new) > broyden.stall().clone()*c0.clone() {
+                // the step is discarded and the jacobian recomputed at x
+                (wjac, inverse)=refresh(&x);
+                fresh=true;
+            } else {
+                // change of the weighted function along the step
+                let df=lin_comb(F::one(), &wres, -F::one(), &wres_new);
+                if bad {
+                    inverse=inverse.map(|inverse|secant_update(inverse, &update, &df));
+                } else {
+                    wjac=secant_update(wjac, &df, &update);
+                }
+                x=xnew;
+                wres=wres_new;
+                fresh=false;
+                evaluations.count_saved_jacobian();
+            }
+            history.record(&x, c0, update_norm);
+            iter+=1;
+        }
+        Ok(history.finish(x, TerminationReason::MaxIterations, evaluations))
+    }
+}
+
+#[cfg(test)]
+fn broyden_test_function(x:[f64;3]) -> [f64;3] {
+    [x[0] + 0.1*x[1]*x[1] - 1.0 + 0.2*x[2].sin(),
+     x[1] + 0.1*x[0]*x[0] - 2.0,
+     x[2] + 0.1*x[2]*x[2] - 0.5 + 0.1*x[0]*x[1]]
+}
+
+#[test]
+fn test_broyden_variants() {
+    use crate::{JacobianUpdate, OptimizationOptions, OptimizationOptionsBuilder, ProblemBuilder};
+    let options=|update:JacobianUpdate<f64>|->OptimizationOptions<f64> {
+        OptimizationOptionsBuilder::default()
+            .max_iter(30)
+            .jacobian_update(update)
+            .build()
+            .unwrap()
+    };
+    let solve=|update:JacobianUpdate<f64>|{
+        ProblemBuilder::<f64,_,_,_>::new(broyden_test_function, [0.0; 3])
+            .set_target_to_zero()
+            .set_weights_to_one()
+            .options(options(update))
+            .build().unwrap()
+            .solve_report().unwrap()
+    };
+    let exact=solve(JacobianUpdate::Exact);
+    assert_eq!(*exact.jacobian_evaluations_saved(), 0);
+    for variant in [BroydenVariant::Good, BroydenVariant::Bad] {
+        let report=solve(JacobianUpdate::Broyden(BroydenOptions::new(variant, 0.9)));
+        assert_eq!(report.termination(), &TerminationReason::StepTolerance);
+        assert!(report.jacobian_evaluations() < exact.jacobian_evaluations());
+        assert!(*report.jacobian_evaluations_saved() > 0);
+        for i in 0..3 {
+            assert!((report.solution()[i] - exact.solution()[i]).abs() < 1e-8);
+        }
+    }
+    let update=JacobianUpdate::Broyden(BroydenOptions::default());
+    let x=crate::fsolve(broyden_test_function, [0.0; 3], Some(options(update))).unwrap();
+    assert!(broyden_test_function(x).iter().all(|fi|fi.abs() < 1e-10));
+}
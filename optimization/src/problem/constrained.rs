@@ -0,0 +1,229 @@
+// least squares problems with equality constraints c(x)=0, solved by sequential quadratic programming.
+// every step minimizes the quadratic model of the lagrangian cost/2+Re(lambda^H*c) subject to the
+// linearized constraints, the multipliers lambda are those of the last step. the hessian of the model
+// is jac^H*jac plus the hessian of Re(lambda^H*c), which is computed by centered differences of
+// a^H*lambda. if the model has no positive curvature along the step, the step of the gauss-newton
+// model without the constraint hessian is taken instead. the step is shortened until the l1 merit
+// function cost/2+nu*|c|_1 decreases sufficiently, nu is kept at least twice the largest multiplier.
+// bounds and robust losses of the problem are not used
+use num_traits::{One, Zero};
+use container_traits::{AnyParameters, Concat, IntoParameters, Iter, LinearContainerConstructError as LCCE};
+
+use algebra_traits::{CastFromf64, Norm, RealNumber, Scalar};
+
+use algebra::VectorDyn;
+
+use matrix::MatrixDyn;
+use matrix_traits::MatrixView;
+use crate::dvec::{adjoint_mat_vec, gram, kkt_matrix, lin_comb, mat_lin_comb, mat_vec, norm1, re_dot, scale};
+use crate::jacobian::adjoint_product_hessian;
+use crate::{into_dvec, LineSearchOptions, OptimizationError, SolveReport, StepControl, TerminationReason};
+use crate::report::{Evaluations, History};
+use super::{cost, Problem};
+
+pub struct ConstrainedProblem<F    : Scalar,
+                              X,
+                              Y,
+                              Func : Fn(X) -> Y,
+                              Cons> {
+    problem: Problem<F,X,Y,Func>,
+    constraint: Cons,
+}
+
+impl<F    : Scalar,
+     X    : Clone,
+     Y    : Clone,
+     Func : Fn(X) -> Y> Problem<F,X,Y,Func> {
+    pub fn constrain<C    : IntoParameters<F>,
+                     Cons : Fn(X) -> C>(self, constraint:Cons) -> ConstrainedProblem<F,X,Y,Func,Cons> {
+        ConstrainedProblem{problem:self, constraint}
+    }
+}
+
+impl<F    : Scalar,
+     X    : AnyParameters<F,LCCE>+Clone,
+     Y    : IntoParameters<F>+Clone,
+     Func : Fn(X) -> Y,
+     C    : IntoParameters<F>+Clone,
+     Cons : Fn(X) -> C> ConstrainedProblem<F,X,Y,Func,Cons> {
+
+    fn numerical_constraint_derivative(&self, x:X) -> MatrixDyn<F> {
+        crate::jacobian(&self.constraint, x, self.problem.options.fd().clone())
+    }
+
+    // returns the solution and the lagrange multipliers
+    pub fn solve(&self) -> Result<(X, VectorDyn<F>), OptimizationError<F,X>> {
+        let (report, multipliers)=self.solve_report()?;
+        Ok((self.problem.solution(report)?, multipliers))
+    }
+
+    pub fn solve_with_der(&self,
+                          derivative:impl Fn(X) -> MatrixDyn<F>,
+                          constraint_derivative:impl Fn(X) -> MatrixDyn<F>) -> Result<(X, VectorDyn<F>), OptimizationError<F,X>> {
+        let (report, multipliers)=self.solve_with_der_report(derivative, constraint_derivative)?;
+        Ok((self.problem.solution(report)?, multipliers))
+    }
+
+    pub fn solve_report(&self) -> Result<(SolveReport<F::RealType,X>, VectorDyn<F>), OptimizationError<F,X>> {
+        let evaluations=Evaluations::default();
+        self.solve_counted(|x:X|self.problem.numerical_derivative(x, &evaluations),
+                           |x:X|self.numerical_constraint_derivative(x),
+                           &evaluations)
+    }
+
+    fn eval_constraint(&self, x:&X) -> VectorDyn<F> {
+        into_dvec::<F,C>((self.constraint)(x.clone()))
+    }
+
+    // the iteration stops with GradientTolerance if the constraint violation is smaller than
+    // constraint_tolerance and the gradient of the lagrangian is smaller than stationarity_tolerance,
+    // and with StepTolerance if the update is smaller than target_cost at a feasible x
+    pub fn solve_with_der_report(&self,
+                                 derivative:impl Fn(X) -> MatrixDyn<F>,
+                                 constraint_derivative:impl Fn(X) -> MatrixDyn<F>) -> Result<(SolveReport<F::RealType,X>, VectorDyn<F>), OptimizationError<F,X>> {
+        self.solve_counted(derivative, constraint_derivative, &Evaluations::default())
+    }
+
+    fn solve_counted(&self,
+                     derivative:impl Fn(X) -> MatrixDyn<F>,
+                     constraint_derivative:impl Fn(X) -> MatrixDyn<F>,
+                     evaluations:&Evaluations) -> Result<(SolveReport<F::RealType,X>, VectorDyn<F>), OptimizationError<F,X>> {
+        let problem=&self.problem;
+        let opts=&problem.options;
+        let ls=match opts.step_control() {
+            StepControl::LineSearch(ls) => ls.clone(),
+            _ => LineSearchOptions::default(),
+        };
+        let mut x=problem.first_guess.clone();
+        let mut multipliers=VectorDyn::from_iter(self.eval_constraint(&x).iter().map(|_|F::zero()));
+        let mut nu=F::RealType::zero();
+        let mut history=History::new(problem.observer.as_ref());
+        let mut iter:u8=0;
+        while &iter < opts.max_iter() {
+            evaluations.count_jacobian();
+            let wjac=problem.weighted_jacobian(derivative(x.clone()));
+            let wres=problem.weighted_residual(x.clone(), evaluations)?;
+            let c=self.eval_constraint(&x);
+            let a=constraint_derivative(x.clone());
+            let n=wjac.ncols();
+            let gradient=adjoint_mat_vec(&wjac, &wres);
+            let infeasibility=c.norm().into_signed();
+            let stationarity=lin_comb(F::one(), &gradient, -F::one(), &adjoint_mat_vec(&a, &multipliers)).norm().into_signed();
+            if &infeasibility < opts.constraint_tolerance() && &stationarity < opts.stationarity_tolerance() {
+                return Ok((history.finish(x, TerminationReason::GradientTolerance, evaluations), multipliers));
+            }
+            let gauss_newton=gram(&wjac);
+            let rhs=gradient.concat(scale(-F::one(), &c));
+            // the step and the multipliers of the quadratic program with the hessian h
+            let qp_step=|h:MatrixDyn<F>|{
+                let kkt=kkt_matrix(&h, &a);
+                match crate::try_solve_least_squares(kkt.clone(), rhs.clone()) {
+                    Some(sol) => {
+                        let update=VectorDyn::from_iter(sol.iter().take(n).cloned());
+                        let curvature=re_dot(&update, &mat_vec(&h, &update));
+                        Ok((update, VectorDyn::from_iter(sol.iter().skip(n).cloned()), curvature))
+                    },
+                    None => Err(OptimizationError::MatrixNotFullRank(kkt, x.clone())),
+                }
+            };
+            let (update, lambda)=if multipliers.iter().all(|l|l.is_zero()) {
+                let (update, lambda, _)=qp_step(gauss_newton)?;
+                (update, lambda)
+            } else {
+                let b=adjoint_product_hessian(&constraint_derivative, &x, &multipliers, opts.fd().clone());
+                let h=mat_lin_comb(F::one(), &gauss_newton, F::one(), &b);
+                match qp_step(h)? {
+                    (update, lambda, curvature) if curvature.is_positive() => (update, lambda),
+                    _ => {
+                        let (update, lambda, _)=qp_step(gauss_newton)?;
+                        (update, lambda)
+                    },
+                }
+            };
+            multipliers=lambda;
+            let update_norm=update.norm().into_signed();
+            if &update_norm < opts.target_cost() && &infeasibility < opts.constraint_tolerance() {
+                return Ok((history.finish(x, TerminationReason::StepTolerance, evaluations), multipliers));
+            }
+            let largest=multipliers.iter()
+                                   .map(|l|l.norm().into_signed())
+                                   .fold(F::RealType::zero(),|acc,l|if l > acc { l } else { acc });
+            let two=F::RealType::from_f64(2.0);
+            if nu < two.clone()*largest.clone() {
+                nu=two*largest;
+            }
+            let merit=|wres:&VectorDyn<F>, c:&VectorDyn<F>|cost(wres)*F::RealType::from_f64(0.5)+nu.clone()*norm1(c);
+            let m0=merit(&wres, &c);
+            // directional derivative of the merit function along the update
+            let slope=-re_dot(&mat_vec(&wjac, &update), &wres)-nu.clone()*norm1(&c);
+            let mut t=F::RealType::one();
+            let mut accepted=None;
+            while &t >= ls.min_step() {
+                let xt=Problem::<F,X,Y,Func>::try_update(x.clone(), scale(F::from(t.clone()), &update))?;
+                let mt=merit(&problem.weighted_residual(xt.clone(), evaluations)?, &self.eval_constraint(&xt));
+                if mt < m0 && mt <= m0.clone()+ls.c1().clone()*t.clone()*slope.clone() {
+                    accepted=Some(xt);
+                    break;
+                }
+                t=t*ls.shrink().clone();
+            }
+            match accepted {
+                Some(xt) => {
+                    x=xt;
+                    history.record(&x, cost(&wres), t*update_norm);
+                },
+                // the merit function can not be decreased along the update
+                None => { return Ok((history.finish(x, TerminationReason::StepTolerance, evaluations), multipliers)); }
+            }
+            iter+=1;
+        }
+        Ok((history.finish(x, TerminationReason::MaxIterations, evaluations), multipliers))
+    }
+}
+
+#[test]
+fn test_closest_point_on_sphere() {
+    use crate::{OptimizationOptionsBuilder, ProblemBuilder};
+    let p=[1.0, 2.0, 2.0];
+    let opts=OptimizationOptionsBuilder::default()
+        .max_iter(20)
+        .build()
+        .unwrap();
+    let (x, multipliers)=ProblemBuilder::<f64,_,_,_>::new(|x:[f64;3]|x, [1.0, 0.0, 0.0])
+        .target(p)
+        .set_weights_to_one()
+        .options(opts)
+        .build().unwrap()
+        .constrain(|x:[f64;3]|[x.iter().map(|xi|xi*xi).sum::<f64>() - 1.0])
+        .solve().unwrap();
+    for i in 0..3 {
+        assert!((x[i] - p[i]/3.0).abs() < 1e-8);
+    }
+    // x-p+2*lambda*x=0
+    assert!((multipliers[0] - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_closest_point_on_sphere_from_generic_start() {
+    use crate::{OptimizationOptionsBuilder, ProblemBuilder};
+    // the first gauss-newton step leads to a negative multiplier, for which the
+    // lagrangian has negative curvature
+    let p=[1.0, 2.0, 2.0];
+    let opts=OptimizationOptionsBuilder::default()
+        .max_iter(20)
+        .build()
+        .unwrap();
+    let (report, multipliers)=ProblemBuilder::<f64,_,_,_>::new(|x:[f64;3]|x, [-0.6, 0.3, -0.4])
+        .target(p)
+        .set_weights_to_one()
+        .options(opts)
+        .build().unwrap()
+        .constrain(|x:[f64;3]|[x.iter().map(|xi|xi*xi).sum::<f64>() - 1.0])
+        .solve_report().unwrap();
+    assert_eq!(report.termination(), &TerminationReason::GradientTolerance);
+    let x=report.solution();
+    for i in 0..3 {
+        assert!((x[i] - p[i]/3.0).abs() < 1e-8);
+    }
+    assert!((multipliers[0] - 1.0).abs() < 1e-6);
+}
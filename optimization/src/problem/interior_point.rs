@@ -0,0 +1,273 @@
+// least squares problems with inequality constraints g(x)<=0, solved by a primal-dual interior point method.
+// with slacks s>0 and multipliers z>0 the perturbed kkt conditions
+// jac^T*res-a^T*z=0, g(x)+s=0 and s_i*z_i=mu
+// are linearized, the hessian of the lagrangian cost/2+z^T*g is jac^T*jac+sum z_i*hess(g_i).
+// the steps are shortened by a backtracking line search on the barrier merit function
+// cost/2-mu*sum ln(s_i)+nu*|g+s|_1, mu is decreased by a constant factor in every iteration
+use num_traits::{One, Zero};
+use container_traits::{AnyParameters, FromFn, IntoParameters, Iter, Len, LinearContainerConstructError as LCCE};
+
+use algebra_traits::{CastFromf64, Norm, RealNumber, TryDiv, TryLog};
+
+use algebra::VectorDyn;
+
+use matrix::MatrixDyn;
+use matrix_traits::MatrixView;
+use crate::dvec::{adjoint_mat_mul, adjoint_mat_vec, dot, gram, lin_comb, mat_lin_comb, mat_vec, norm1, re_dot, scale};
+use crate::jacobian::adjoint_product_hessian;
+use crate::{into_dvec, LineSearchOptions, OptimizationError, SolveReport, StepControl, TerminationReason};
+use crate::report::{Evaluations, History};
+use super::{cost, Problem};
+
+pub struct InequalityConstrainedProblem<F    : RealNumber,
+                                        X,
+                                        Y,
+                                        Func : Fn(X) -> Y,
+                                        Cons> {
+    problem: Problem<F,X,Y,Func>,
+    constraint: Cons,
+}
+
+impl<F    : RealNumber,
+     X    : Clone,
+     Y    : Clone,
+     Func : Fn(X) -> Y> Problem<F,X,Y,Func> {
+    pub fn constrain_inequalities<G    : IntoParameters<F>,
+                                  Cons : Fn(X) -> G>(self, constraint:Cons) -> InequalityConstrainedProblem<F,X,Y,Func,Cons> {
+        InequalityConstrainedProblem{problem:self, constraint}
+    }
+}
+
+#[derive(Clone, Debug, derive_getters::Getters)]
+pub struct InteriorPointReport<R,X> {
+    report: SolveReport<R,X>,
+    multipliers: VectorDyn<R>,
+    // largest value of g at the solution if positive, zero otherwise
+    constraint_violation: R,
+}
+
+impl<R,X> InteriorPointReport<R,X> {
+    pub fn into_report(self) -> SolveReport<R,X> {
+        self.report
+    }
+}
+
+// largest alpha in (0,1] with v+alpha*dv >= (1-tau)*v
+fn step_to_boundary<R:RealNumber>(v:&VectorDyn<R>, dv:&VectorDyn<R>, tau:R) -> R {
+    v.iter()
+     .zip(dv.iter())
+     .filter(|(_,dvi)|dvi.is_negative())
+     .filter_map(|(vi,dvi)|(-tau.clone()*vi.clone()).try_div(dvi.clone()).ok())
+     .fold(R::one(),|acc,alpha|if alpha < acc { alpha } else { acc })
+}
+
+impl<F    : RealNumber,
+     X    : AnyParameters<F,LCCE>+Clone,
+     Y    : IntoParameters<F>+Clone,
+     Func : Fn(X) -> Y,
+     G    : IntoParameters<F>+Clone,
+     Cons : Fn(X) -> G> InequalityConstrainedProblem<F,X,Y,Func,Cons> {
+
+    fn eval_constraint(&self, x:&X) -> VectorDyn<F> {
+        into_dvec::<F,G>((self.constraint)(x.clone()))
+    }
+
+    fn numerical_constraint_derivative(&self, x:X) -> MatrixDyn<F> {
+        crate::jacobian(&self.constraint, x, self.problem.options.fd().clone())
+    }
+
+    pub fn solve(&self) -> Result<X, OptimizationError<F,X>> {
+        let report=self.solve_report()?;
+        self.problem.solution(report.into_report())
+    }
+
+    pub fn solve_with_der(&self,
+                          derivative:impl Fn(X) -> MatrixDyn<F>,
+                          constraint_derivative:impl Fn(X) -> MatrixDyn<F>) -> Result<X, OptimizationError<F,X>> {
+        let report=self.solve_with_der_report(derivative, constraint_derivative)?;
+        self.problem.solution(report.into_report())
+    }
+
+    pub fn solve_report(&self) -> Result<InteriorPointReport<F,X>, OptimizationError<F,X>> {
+        let evaluations=Evaluations::default();
+        self.solve_counted(|x:X|self.problem.numerical_derivative(x, &evaluations),
+                           |x:X|self.numerical_constraint_derivative(x),
+                           &evaluations)
+    }
+
+    pub fn solve_with_der_report(&self,
+                                 derivative:impl Fn(X) -> MatrixDyn<F>,
+                                 constraint_derivative:impl Fn(X) -> MatrixDyn<F>) -> Result<InteriorPointReport<F,X>, OptimizationError<F,X>> {
+        self.solve_counted(derivative, constraint_derivative, &Evaluations::default())
+    }
+
+    fn solve_counted(&self,
+                     derivative:impl Fn(X) -> MatrixDyn<F>,
+                     constraint_derivative:impl Fn(X) -> MatrixDyn<F>,
+                     evaluations:&Evaluations) -> Result<InteriorPointReport<F,X>, OptimizationError<F,X>> {
+        let problem=&self.problem;
+        let opts=&problem.options;
+        let ls=match opts.step_control() {
+            StepControl::LineSearch(ls) => ls.clone(),
+            _ => LineSearchOptions::default(),
+        };
+        let sigma=F::from_f64(0.1);
+        let tau=F::from_f64(0.995);
+        let half=F::from_f64(0.5);
+        let two=F::from_f64(2.0);
+        let mut x=problem.first_guess.clone();
+        // the slacks start at -g(x) if x is strictly feasible
+        let mut s=VectorDyn::from_iter(self.eval_constraint(&x).iter().map(|gi|if -gi.clone() > F::one() { -gi.clone() } else { F::one() }));
+        let mut z=VectorDyn::from_iter(s.iter().map(|_|F::one()));
+        let m=F::from_f64(s.len() as f64);
+        let mut nu=F::zero();
+        // the slacks stay positive by the fraction to the boundary rule unless they underflow
+        let div=|a:F, s:&VectorDyn<F>, i:usize|a.try_div(s[i].clone()).map_err(|_|OptimizationError::<F,X>::VanishedSlack(i));
+        let finish=|history:History<F,X>, x:X, z:VectorDyn<F>, reason:TerminationReason|{
+            let constraint_violation=self.eval_constraint(&x)
+                                         .iter()
+                                         .fold(F::zero(),|acc,gi|if gi > &acc { gi.clone() } else { acc });
+            InteriorPointReport{report:history.finish(x, reason, evaluations),
+                                multipliers:z,
+                                constraint_violation}
+        };
+        let mut history=History::new(problem.observer.as_ref());
+        let mut iter:u8=0;
+        while &iter < opts.max_iter() {
+            evaluations.count_jacobian();
+            let wjac=problem.weighted_jacobian(derivative(x.clone()));
+            let wres=problem.weighted_residual(x.clone(), evaluations)?;
+            let g=self.eval_constraint(&x);
+            let a=constraint_derivative(x.clone());
+            let n=wjac.ncols();
+            // without constraints the mean complementarity is zero
+            let complementarity=dot(&s, &z).try_div(m.clone()).unwrap_or(F::zero());
+            let mu=sigma.clone()*complementarity.clone();
+            let infeasibility=lin_comb(F::one(), &g, F::one(), &s).norm().into_signed();
+            let gradient=adjoint_mat_vec(&wjac, &wres);
+            let stationarity=lin_comb(F::one(), &gradient, -F::one(), &adjoint_mat_vec(&a, &z)).norm().into_signed();
+            let complementary=&complementarity < opts.complementarity_tolerance();
+            let feasible=&infeasibility < opts.constraint_tolerance();
+            if complementary && feasible && &stationarity < opts.stationarity_tolerance() {
+                return Ok(finish(history, x, z, TerminationReason::GradientTolerance));
+            }
+            // the newton system, condensed to dx, is
+            // (jac^T*jac+b+a^T*(z/s)*a)*dx=jac^T*res-a^T*w with w=z+(mu+z*g)/s
+            let ratio=(0..s.len()).map(|i|div(z[i].clone(), &s, i)).collect::<Result<Vec<F>,_>>()?;
+            let w=(0..s.len()).map(|i|div(mu.clone()+z[i].clone()*g[i].clone(), &s, i).map(|q|z[i].clone()+q))
+                              .collect::<Result<Vec<F>,_>>()?;
+            let rhs=lin_comb(F::one(), &gradient, -F::one(), &adjoint_mat_vec(&a, &VectorDyn::from_iter(w)));
+            let scaled=MatrixDyn::from_fn((a.nrows(), n),|(k,j)|ratio[k].clone()*a[(k,j)].clone());
+            let base=mat_lin_comb(F::one(), &gram(&wjac), F::one(), &adjoint_mat_mul(&a, &scaled));
+            let b=adjoint_product_hessian(&constraint_derivative, &x, &z, opts.fd().clone());
+            let lhs=mat_lin_comb(F::one(), &base, F::one(), &b);
+            let newton_step=|lhs:MatrixDyn<F>|match crate::try_solve_least_squares(lhs.clone(), rhs.clone()) {
+                Some(dx) => Ok(dx),
+                None => Err(OptimizationError::MatrixNotFullRank(lhs, x.clone())),
+            };
+            // the hessian of the constraints is dropped if it makes the step uphill
+            let dx=newton_step(lhs.clone())?;
+            let dx=if re_dot(&dx, &mat_vec(&lhs, &dx)).is_positive() { dx } else { newton_step(base)? };
+            let adx=mat_vec(&a, &dx);
+            let dz=VectorDyn::from_iter((0..s.len()).map(|i|div(mu.clone()+z[i].clone()*(g[i].clone()+adx[i].clone()), &s, i))
+                                                    .collect::<Result<Vec<F>,_>>()?);
+            let ds=VectorDyn::from_iter((0..s.len()).map(|i|-g[i].clone()-s[i].clone()-adx[i].clone()));
+            let alpha_primal=step_to_boundary(&s, &ds, tau.clone());
+            let alpha_dual=step_to_boundary(&z, &dz, tau.clone());
+            let dx_norm=dx.norm().into_signed();
+            if &(alpha_primal.clone()*dx_norm.clone()) < opts.target_cost() && complementary && feasible {
+                return Ok(finish(history, x, z, TerminationReason::StepTolerance));
+            }
+            let znew=lin_comb(F::one(), &z, alpha_dual, &dz);
+            let largest=znew.iter().fold(F::zero(),|acc,zi|if zi > &acc { zi.clone() } else { acc });
+            if nu < two.clone()*largest.clone() {
+                nu=two.clone()*largest;
+            }
+            let merit=|wres:&VectorDyn<F>, g:&VectorDyn<F>, s:&VectorDyn<F>|{
+                let barrier=s.iter()
+                             .enumerate()
+                             .map(|(i,si)|si.clone().try_log().map_err(|_|i))
+                             .try_fold(F::zero(),|acc,li|li.map(|li|acc+li))?;
+                Ok::<F,usize>(half.clone()*cost(wres)-mu.clone()*barrier+nu.clone()*norm1(&lin_comb(F::one(), g, F::one(), s)))
+            };
+            let merit0=merit(&wres, &g, &s).map_err(OptimizationError::VanishedSlack)?;
+            let barrier_slope=(0..s.len()).map(|i|div(ds[i].clone(), &s, i))
+                                          .try_fold(F::zero(),|acc,q|q.map(|q|acc+q))?;
+            let slope=-re_dot(&mat_vec(&wjac, &dx), &wres)-mu.clone()*barrier_slope-nu.clone()*norm1(&lin_comb(F::one(), &g, F::one(), &s));
+            let mut t=alpha_primal;
+            let accepted=loop {
+                if &t < ls.min_step() {
+                    break None;
+                }
+                let xt=Problem::<F,X,Y,Func>::try_update(x.clone(), scale(t.clone(), &dx))?;
+                let st=lin_comb(F::one(), &s, t.clone(), &ds);
+                let wrest=problem.weighted_residual(xt.clone(), evaluations)?;
+                // a trial whose slacks underflow is rejected
+                if let Ok(merit_t)=merit(&wrest, &self.eval_constraint(&xt), &st) {
+                    if merit_t <= merit0.clone()+ls.c1().clone()*t.clone()*slope.clone() {
+                        break Some((xt, st, wrest));
+                    }
+                }
+                t=t*ls.shrink().clone();
+            };
+            let Some((xt, st, wrest))=accepted else {
+                return Ok(finish(history, x, z, TerminationReason::StepTolerance));
+            };
+            x=xt;
+            s=st;
+            z=znew;
+            history.record(&x, cost(&wrest), t*dx_norm);
+            iter+=1;
+        }
+        Ok(finish(history, x, z, TerminationReason::MaxIterations))
+    }
+}
+
+#[test]
+fn test_closest_point_in_disk_segment() {
+    use crate::{OptimizationOptionsBuilder, ProblemBuilder};
+    let opts=OptimizationOptionsBuilder::default()
+        .max_iter(30)
+        .build()
+        .unwrap();
+    // x^2+y^2 <= 1 and y >= 0.2, both constraints are active at the solution
+    let report=ProblemBuilder::<f64,_,_,_>::new(|x:[f64;2]|x, [0.0, 0.0])
+        .target([2.0, 0.0])
+        .set_weights_to_one()
+        .options(opts)
+        .build().unwrap()
+        .constrain_inequalities(|x:[f64;2]|[x[0]*x[0] + x[1]*x[1] - 1.0, 0.2 - x[1]])
+        .solve_report().unwrap();
+    assert_eq!(report.report().termination(), &TerminationReason::GradientTolerance);
+    let x=report.report().solution();
+    assert!((x[0] - 0.96_f64.sqrt()).abs() < 1e-8);
+    assert!((x[1] - 0.2).abs() < 1e-8);
+    assert!(report.constraint_violation() < &1e-8);
+    // x-p+z_0*(2x_0, 2x_1)+z_1*(0, -1)=0
+    let z0=(2.0 - 0.96_f64.sqrt())/(2.0*0.96_f64.sqrt());
+    assert!((report.multipliers()[0] - z0).abs() < 1e-6);
+    assert!((report.multipliers()[1] - 0.2*(1.0 + 2.0*z0)).abs() < 1e-6);
+}
+
+#[test]
+fn test_closest_point_on_disk_with_curved_constraint_active() {
+    use crate::{OptimizationOptionsBuilder, ProblemBuilder};
+    let opts=OptimizationOptionsBuilder::default()
+        .max_iter(30)
+        .build()
+        .unwrap();
+    // only x^2+y^2 <= 1 is active, the solution is p/|p| with multiplier (|p|-1)/2
+    let report=ProblemBuilder::<f64,_,_,_>::new(|x:[f64;2]|x, [0.3, -0.5])
+        .target([2.0, 1.0])
+        .set_weights_to_one()
+        .options(opts)
+        .build().unwrap()
+        .constrain_inequalities(|x:[f64;2]|[x[0]*x[0] + x[1]*x[1] - 1.0, -x[0] - 2.0])
+        .solve_report().unwrap();
+    assert_eq!(report.report().termination(), &TerminationReason::GradientTolerance);
+    let x=report.report().solution();
+    assert!((x[0] - 2.0/5.0_f64.sqrt()).abs() < 1e-8);
+    assert!((x[1] - 1.0/5.0_f64.sqrt()).abs() < 1e-8);
+    assert!((report.multipliers()[0] - (5.0_f64.sqrt() - 1.0)/2.0).abs() < 1e-6);
+    assert!(report.multipliers()[1].abs() < 1e-6);
+}
@@ -0,0 +1,123 @@
+use num_traits::{One, Zero};
+use container_traits::{AnyParameters, IntoParameters, Iter, LinearContainerConstructError as LCCE};
+
+use algebra_traits::{Norm, RealNumber, Scalar, TryDiv, TrySqrt};
+
+use algebra::VectorDyn;
+
+use matrix::MatrixDyn;
+use crate::{OptimizationError, RobustLoss, SolveReport, TerminationReason};
+use crate::robust_loss::mad_scale;
+use crate::report::{Evaluations, History};
+use super::{cost, Problem};
+
+impl<F    : Scalar,
+     X    : AnyParameters<F,LCCE>+Clone,
+     Y    : IntoParameters<F>+Clone,
+     Func : Fn(X) -> Y> Problem<F,X,Y,Func> {
+
+    // the absolute values of the weighted residuals are measured in units of their robust scale
+    fn robust_weights(&self, loss:&RobustLoss<F::RealType>, x:X, evaluations:&Evaluations) -> Result<VectorDyn<F::RealType>, OptimizationError<F,X>> {
+        let u:Vec<F::RealType>=
+            self.weighted_residual(x, evaluations)?
+                .iter()
+                .map(|ri|ri.norm().into_signed())
+                .collect();
+        let s=mad_scale(&u);
+        // otherwise all residuals vanish
+        let inv=if s.is_positive() { F::RealType::one().try_div(s).unwrap() } else { F::RealType::zero() };
+        Ok(VectorDyn::from_iter(u.into_iter().map(|ui|loss.weight(ui*inv.clone()))))
+    }
+
+    // iteratively reweighted least squares, returns the report and the robust weights.
+    // iterations of the weighted least squares problems are not passed to the observer.
+    // a weighted problem which does not converge within max_iter is not an error,
+    // the reweighting continues from its last iterate
+    pub(super) fn irls(&self,
+                       derivative:impl Fn(X) -> MatrixDyn<F>,
+                       loss:&RobustLoss<F::RealType>,
+                       evaluations:&Evaluations) -> Result<(SolveReport<F::RealType,X>, VectorDyn<F::RealType>), OptimizationError<F,X>> {
+        let opts=&self.options;
+        let tol=opts.target_cost().clone().try_sqrt().unwrap().into_signed();
+        let mut x=self.first_guess.clone();
+        let mut rweights=VectorDyn::from_iter(self.weights.iter().map(|_|F::RealType::one()));
+        let mut history=History::new(self.observer.as_ref());
+        let mut iter:u8=0;
+        while &iter < opts.max_iter() {
+            let weights=VectorDyn::from_iter(
+                self.weights.iter()
+                    .zip(rweights.iter())
+                    .map(|(w,rw)|w.clone()*rw.clone().try_sqrt().unwrap().into_signed()));
+            let reweighted=Problem{function:Box::new(|x:X|(self.function)(x)),
+                                   first_guess:x.clone(),
+                                   target:self.target.clone(),
+                                   weights,
+                                   options:self.options.clone(),
+                                   robust_loss:None,
+                                   bounds:self.bounds.clone(),
+                                   observer:None,
+                                   whitening:self.whitening.clone()};
+            let c0=cost(&reweighted.weighted_residual(x.clone(), evaluations)?);
+            let xnew=reweighted.solve_least_squares(&derivative, evaluations)?.into_solution();
+            let rweights_new=self.robust_weights(loss, xnew.clone(), evaluations)?;
+            let change=rweights.iter()
+                               .zip(rweights_new.iter())
+                               .all(|(a,b)|(a.clone()-b.clone()).norm() < tol);
+            let step_norm=Self::distance(&x, &xnew);
+            x=xnew;
+            rweights=rweights_new;
+            history.record(&x, c0, step_norm);
+            if change {
+                return Ok((history.finish(x, TerminationReason::StepTolerance, evaluations), rweights));
+            }
+            iter+=1;
+        }
+        Ok((history.finish(x, TerminationReason::MaxIterations, evaluations), rweights))
+    }
+
+    // residuals with a small robust weight are treated as outliers
+    pub fn solve_robust_with_der(&self, derivative:impl Fn(X) -> MatrixDyn<F>) -> Result<(X, VectorDyn<F::RealType>), OptimizationError<F,X>> {
+        self.solve_robust_counted(derivative, &Evaluations::default())
+    }
+
+    pub fn solve_robust(&self) -> Result<(X, VectorDyn<F::RealType>), OptimizationError<F,X>> {
+        let evaluations=Evaluations::default();
+        self.solve_robust_counted(|x:X| self.numerical_derivative(x, &evaluations), &evaluations)
+    }
+
+    fn solve_robust_counted(&self,
+                            derivative:impl Fn(X) -> MatrixDyn<F>,
+                            evaluations:&Evaluations) -> Result<(X, VectorDyn<F::RealType>), OptimizationError<F,X>> {
+        match &self.robust_loss {
+            Some(loss) => {
+                let (report, rweights)=self.irls(derivative, loss, evaluations)?;
+                Ok((self.solution(report)?, rweights))
+            },
+            None => {
+                let ones=VectorDyn::from_iter(self.weights.iter().map(|_|F::RealType::one()));
+                self.solve_counted(derivative, evaluations)
+                    .and_then(|report|self.solution(report))
+                    .map(|x|(x,ones))
+            },
+        }
+    }
+}
+
+#[test]
+fn test_line_fit_with_outlier() {
+    use crate::ProblemBuilder;
+    let ts:Vec<f64>=(0..11).map(|i|i as f64).collect();
+    let mut ys:Vec<f64>=ts.iter().map(|t|2.0+0.5*t).collect();
+    ys[7]+=20.0;
+    let f = |p: [f64;2]| ts.iter().map(|t|p[0]+p[1]*t).collect::<Vec<f64>>();
+    let (p, rweights) = ProblemBuilder::<f64,_,_,_>::new(&f, [0.0, 0.0])
+        .target(ys)
+        .set_weights_to_one()
+        .robust_loss(RobustLoss::Tukey(4.685))
+        .build().unwrap()
+        .solve_robust().unwrap();
+    assert!((p[0] - 2.0).abs() < 1e-6);
+    assert!((p[1] - 0.5).abs() < 1e-6);
+    assert!(rweights[7] < 1e-6);
+    assert!(rweights[0] > 0.99);
+}
@@ -0,0 +1,129 @@
+use num_traits::{One, Zero};
+use container_traits::{AnyParameters, Concat, FromElement, FromFn, IntoParameters, LinearContainerConstructError as LCCE};
+
+use algebra_traits::{Norm, Scalar, TrySqrt};
+
+use algebra::VectorDyn;
+
+use matrix::MatrixDyn;
+use matrix_traits::{MatrixTryConstruct, MatrixView};
+use crate::{LevenbergMarquardtOptions, OptimizationError, SolveReport, TerminationReason};
+use crate::report::{Evaluations, History};
+use super::{cost, Problem};
+
+impl<F    : Scalar,
+     X    : AnyParameters<F,LCCE>+Clone,
+     Y    : IntoParameters<F>+Clone,
+     Func : Fn(X) -> Y> Problem<F,X,Y,Func> {
+
+    // minimizes |wjac*update-wres|^2+lambda*|d*update|^2 in every step,
+    // which is solvable even if wjac does not have full rank. d is the identity
+    // or, with marquardt scaling, the diagonal of the largest column norms of wjac
+    pub(super) fn levenberg_marquardt(&self,
+                                      derivative:impl Fn(X) -> MatrixDyn<F>,
+                                      lm:LevenbergMarquardtOptions<F::RealType>,
+                                      evaluations:&Evaluations) -> Result<SolveReport<F::RealType,X>, OptimizationError<F,X>> {
+        let opts=&self.options;
+        let mut x=self.first_guess.clone();
+        let mut history=History::new(self.observer.as_ref());
+        let mut wjac=self.weighted_jacobian(derivative(x.clone()));
+        let mut wres=self.weighted_residual(x.clone(), evaluations)?;
+        let clamp=|lambda:F::RealType|
+            if &lambda < lm.min_damping() {
+                lm.min_damping().clone()
+            } else if &lambda > lm.max_damping() {
+                lm.max_damping().clone()
+            } else {
+                lambda
+            };
+        let mut lambda=clamp(lm.initial_damping().clone());
+        let n=wjac.ncols();
+        let mut scale=VectorDyn::from_element(n, F::RealType::zero());
+        let mut iter:u8=0;
+        while &iter < opts.max_iter() {
+            if let Some(reason) = self.converged(&wjac, &wres) {
+                return Ok(history.finish(x, reason, evaluations));
+            }
+            if *lm.marquardt_scaling() {
+                for j in 0..n {
+                    let col_norm=VectorDyn::from_iter((0..wjac.nrows()).map(|i|wjac[(i,j)].clone())).norm().into_signed();
+                    if col_norm > scale[j] {
+                        scale[j]=col_norm;
+                    }
+                }
+            }
+            // parameters without scale, e.g. if their column vanishes, are damped by lambda
+            let d=|j:usize|if scale[j].is_zero() { F::RealType::one() } else { scale[j].clone() };
+            let sqrt_lambda=lambda.clone().try_sqrt().unwrap().into_signed();
+            let damping=MatrixDyn::<F>::from_fn((n,n),|(i,j)|if i == j { F::from(sqrt_lambda.clone()*d(j)) } else { F::zero() });
+            let a=wjac.clone().try_concat_vertically(damping).unwrap();
+            let b=wres.clone().concat(VectorDyn::from_element(n, F::zero()));
+            let update=match crate::try_solve_least_squares(a,b) {
+                Some(update) => update,
+                None => { return Err(OptimizationError::MatrixNotFullRank(wjac, x)); }
+            };
+            let update_norm=update.norm().into_signed();
+            if &update_norm < opts.target_cost() {
+                return Ok(history.finish(x, TerminationReason::StepTolerance, evaluations));
+            }
+            let xnew=Self::try_update(x.clone(), update)?;
+            let wres_new=self.weighted_residual(xnew.clone(), evaluations)?;
+            let c0=cost(&wres);
+            if cost(&wres_new) < c0 {
+                x=xnew;
+                wres=wres_new;
+                wjac=self.weighted_jacobian(derivative(x.clone()));
+                lambda=clamp(lambda*lm.decrease().clone());
+            } else {
+                lambda=clamp(lambda*lm.increase().clone());
+            }
+            history.record(&x, c0, update_norm);
+            iter+=1;
+        }
+        Ok(history.finish(x, TerminationReason::MaxIterations, evaluations))
+    }
+}
+
+#[cfg(test)]
+fn lm_options(max_iter:u8) -> crate::OptimizationOptions<f64> {
+    lm_options_with(max_iter, LevenbergMarquardtOptions::default())
+}
+
+#[cfg(test)]
+fn lm_options_with(max_iter:u8, lm:LevenbergMarquardtOptions<f64>) -> crate::OptimizationOptions<f64> {
+    crate::OptimizationOptionsBuilder::default()
+        .method(crate::Method::LevenbergMarquardt(lm))
+        .max_iter(max_iter)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_lm_rank_deficient_first_guess() {
+    use algebra::Vector2;
+    let f = |x: Vector2<f64>| Vector2::from([x[0]*x[0] + x[1]*x[1] - 1.0, x[0] - x[1]]);
+    let x0 = Vector2::from([0.0, 0.0]);
+    assert!(crate::fsolve(f, x0.clone(), None).is_err());
+    let xsol = crate::fsolve(f, x0, Some(lm_options(100))).unwrap();
+    let expected = 0.5_f64.sqrt();
+    assert!((xsol[0] - expected).abs() < 1e-6);
+    assert!((xsol[1] - expected).abs() < 1e-6);
+}
+
+#[test]
+fn test_lm_inverse_problem() {
+    let f = |x: f64| x * x;
+    let xsol = crate::solve_inverse_problem(f, 2.0, 2.0, Some(lm_options(100))).unwrap();
+    assert!((xsol - 2.0_f64.sqrt()).abs() < 1e-8);
+}
+
+#[test]
+fn test_lm_damping_bounds_and_scaling() {
+    // the second parameter is in units a million times smaller than the first
+    let f = |p: [f64;2]| [p[0] - 1.0, 1e-6*p[1] - 2.0, p[0]*1e-6*p[1] - 2.0];
+    // the damping stays at its upper bound and is scaled by the columns of the jacobian
+    let lm=LevenbergMarquardtOptions::new(1e20, 10.0, 1.0, 1e-12, 1e-2, true);
+    let xsol = crate::fsolve(f, [0.0, 0.0], Some(lm_options_with(200, lm))).unwrap();
+    assert!((xsol[0] - 1.0).abs() < 1e-6);
+    assert!((xsol[1] - 2e6).abs() < 1e-2);
+}
@@ -0,0 +1,74 @@
+use num_traits::One;
+use container_traits::{AnyParameters, IntoParameters, LinearContainerConstructError as LCCE};
+
+use algebra_traits::{CastFromf64, Scalar};
+
+use algebra::VectorDyn;
+
+use matrix::MatrixDyn;
+use crate::dvec::{mat_vec, re_dot, scale};
+use crate::{LineSearchOptions, OptimizationError};
+use crate::report::Evaluations;
+use super::{cost, Problem};
+
+impl<F    : Scalar,
+     X    : AnyParameters<F,LCCE>+Clone,
+     Y    : IntoParameters<F>+Clone,
+     Func : Fn(X) -> Y> Problem<F,X,Y,Func> {
+
+    // returns None if no step along update decreases the weighted cost
+    pub(super) fn backtracking(&self,
+                               x:X,
+                               wjac:&MatrixDyn<F>,
+                               wres:&VectorDyn<F>,
+                               update:VectorDyn<F>,
+                               ls:LineSearchOptions<F::RealType>,
+                               evaluations:&Evaluations) -> Result<Option<X>, OptimizationError<F,X>> {
+        let c0=cost(wres);
+        // derivative of t -> cost(x+t*update) at t=0
+        let slope=re_dot(&mat_vec(wjac, &update), wres)*F::RealType::from_f64(-2.0);
+        let mut t=F::RealType::one();
+        while &t >= ls.min_step() {
+            let xt=Self::try_update(x.clone(), scale(F::from(t.clone()), &update))?;
+            let ct=cost(&self.weighted_residual(xt.clone(), evaluations)?);
+            if ct < c0 && ct <= c0.clone()+ls.c1().clone()*t.clone()*slope.clone() {
+                return Ok(Some(xt));
+            }
+            t=t*ls.shrink().clone();
+        }
+        Ok(None)
+    }
+}
+
+#[test]
+fn test_line_search_decreases_cost() {
+    use crate::{OptimizationOptionsBuilder, StepControl};
+    // full gauss-newton steps for atan overshoot and diverge for this first guess
+    let f = |x: f64| x.atan();
+    let opts = OptimizationOptionsBuilder::default()
+        .step_control(StepControl::LineSearch(LineSearchOptions::default()))
+        .max_iter(50)
+        .build()
+        .unwrap();
+    let xsol = crate::fsolve(f, 3.0, Some(opts)).unwrap();
+    assert!(xsol.abs() < 1e-8);
+}
+
+#[test]
+fn test_default_step_control_decreases_cost() {
+    use crate::{OptimizationOptionsBuilder, StepControl};
+    let f = |x: f64| x.atan();
+    let opts = OptimizationOptionsBuilder::default()
+        .max_iter(50)
+        .build()
+        .unwrap();
+    let xsol = crate::fsolve(f, 3.0, Some(opts)).unwrap();
+    assert!(xsol.abs() < 1e-8);
+    // unguarded full steps diverge
+    let opts = OptimizationOptionsBuilder::default()
+        .step_control(StepControl::FullStep)
+        .max_iter(50)
+        .build()
+        .unwrap();
+    assert!(crate::fsolve(f, 3.0, Some(opts)).is_err());
+}
@@ -0,0 +1,179 @@
+// gauss-newton for unknowns on a manifold, e.g. poses. the jacobian is taken with respect to the
+// local parameters around the current iterate and the update is applied in this chart, such that
+// the iterates never leave the manifold. only the local parameters of X are needed
+use std::cell::RefCell;
+use num_traits::{One, Zero};
+use container_traits::{FromElement, IntoLocalParameters, IntoParameters, LocalParameters, TryFromLocalParameters};
+
+use algebra_traits::{CastFromf64, Norm, Scalar};
+
+use algebra::VectorDyn;
+
+use matrix::MatrixDyn;
+use crate::dvec::{mat_vec, re_dot, scale};
+use crate::{into_dvec, jacobian_dvec, OptimizationError, SolveReport, StepControl, TerminationReason};
+use crate::report::{Evaluations, History};
+use super::{cost, Problem};
+
+impl<F    : Scalar,
+     X    : Clone,
+     Y    : IntoParameters<F>+Clone,
+     Func : Fn(X) -> Y> Problem<F,X,Y,Func> {
+
+    // the point with the local parameters delta in the chart around x
+    fn retract<E>(x:X, delta:VectorDyn<F>) -> Result<X, OptimizationError<F,X,E>> where X : LocalParameters<F,E> {
+        <X as TryFromLocalParameters<F,E>>::try_from_iter(x, delta.into_iter())
+            .map_err(OptimizationError::ContainerConstruct)
+    }
+
+    // a failing retraction of a finite difference step is returned after the jacobian is evaluated
+    fn numerical_local_derivative<E>(&self, x:X, evaluations:&Evaluations) -> Result<MatrixDyn<F>, OptimizationError<F,X,E>> where X : LocalParameters<F,E> {
+        let n=x.clone().into_local_parameters(x.clone()).len();
+        let error=RefCell::new(None);
+        let jac=jacobian_dvec(|delta|match Self::retract::<E>(x.clone(), delta) {
+                                  Ok(xd) => into_dvec(self.eval(xd, evaluations)),
+                                  Err(e) => {
+                                      error.borrow_mut().get_or_insert(e);
+                                      into_dvec(self.eval(x.clone(), evaluations))
+                                  },
+                              },
+                              VectorDyn::from_element(n, F::zero()),
+                              self.options.fd().clone());
+        match error.into_inner() {
+            Some(e) => Err(e),
+            None    => Ok(jac),
+        }
+    }
+
+    fn manifold_solution<E>(&self, report:SolveReport<F::RealType,X>) -> Result<X, OptimizationError<F,X,E>> {
+        match report.termination() {
+            TerminationReason::MaxIterations => Err(OptimizationError::MaximalIteration(*self.options.max_iter())),
+            _                                => Ok(report.into_solution()),
+        }
+    }
+
+    pub fn solve_on_manifold<E>(&self) -> Result<X, OptimizationError<F,X,E>> where X : LocalParameters<F,E> {
+        let report=self.solve_on_manifold_report()?;
+        self.manifold_solution(report)
+    }
+
+    // the derivative is taken with respect to the local parameters around its argument
+    pub fn solve_on_manifold_with_der<E>(&self, derivative:impl Fn(X) -> MatrixDyn<F>) -> Result<X, OptimizationError<F,X,E>> where X : LocalParameters<F,E> {
+        let report=self.solve_on_manifold_with_der_report(derivative)?;
+        self.manifold_solution(report)
+    }
+
+    pub fn solve_on_manifold_report<E>(&self) -> Result<SolveReport<F::RealType,X>, OptimizationError<F,X,E>> where X : LocalParameters<F,E> {
+        let evaluations=Evaluations::default();
+        self.manifold_gauss_newton(|x:X|self.numerical_local_derivative::<E>(x, &evaluations), &evaluations)
+    }
+
+    pub fn solve_on_manifold_with_der_report<E>(&self, derivative:impl Fn(X) -> MatrixDyn<F>) -> Result<SolveReport<F::RealType,X>, OptimizationError<F,X,E>> where X : LocalParameters<F,E> {
+        self.manifold_gauss_newton(|x:X|Ok(derivative(x)), &Evaluations::default())
+    }
+
+    // gauss-newton steps, with backtracking if the step control is a line search.
+    // the step norms of the report are measured in the local parameters
+    fn manifold_gauss_newton<E>(&self,
+                                derivative:impl Fn(X) -> Result<MatrixDyn<F>, OptimizationError<F,X,E>>,
+                                evaluations:&Evaluations) -> Result<SolveReport<F::RealType,X>, OptimizationError<F,X,E>> where X : LocalParameters<F,E> {
+        let opts=&self.options;
+        let mut x=self.first_guess.clone();
+        let mut history=History::new(self.observer.as_ref());
+        let mut iter:u8=0;
+        while &iter < opts.max_iter() {
+            evaluations.count_jacobian();
+            let wjac=self.weighted_jacobian(derivative(x.clone())?);
+            let wres=self.weighted_difference::<E>(self.eval(x.clone(), evaluations))?;
+            if let Some(reason) = self.converged(&wjac, &wres) {
+                return Ok(history.finish(x, reason, evaluations));
+            }
+            let update=match crate::try_solve_least_squares(wjac.clone(),wres.clone()) {
+                Some(update) => update,
+                None => { return Err(OptimizationError::MatrixNotFullRank(wjac, x)); }
+            };
+            let update_norm=update.norm().into_signed();
+            if &update_norm < opts.target_cost() {
+                return Ok(history.finish(x, TerminationReason::StepTolerance, evaluations));
+            }
+            let (xnew, step_norm)=match opts.step_control() {
+                StepControl::LineSearch(ls) => {
+                    let c0=cost(&wres);
+                    let slope=re_dot(&mat_vec(&wjac, &update), &wres)*F::RealType::from_f64(-2.0);
+                    let mut t=F::RealType::one();
+                    let mut accepted=None;
+                    while &t >= ls.min_step() {
+                        let xt=Self::retract::<E>(x.clone(), scale(F::from(t.clone()), &update))?;
+                        let ct=cost(&self.weighted_difference::<E>(self.eval(xt.clone(), evaluations))?);
+                        if ct < c0 && ct <= c0.clone()+ls.c1().clone()*t.clone()*slope.clone() {
+                            accepted=Some((xt, t.clone()*update_norm.clone()));
+                            break;
+                        }
+                        t=t*ls.shrink().clone();
+                    }
+                    match accepted {
+                        Some(accepted) => accepted,
+                        // no decrease along the update possible
+                        None => { return Ok(history.finish(x, TerminationReason::StepTolerance, evaluations)); }
+                    }
+                },
+                _ => (Self::retract::<E>(x, update)?, update_norm)
+            };
+            x=xnew;
+            history.record(&x, cost(&wres), step_norm);
+            iter+=1;
+        }
+        Ok(history.finish(x, TerminationReason::MaxIterations, evaluations))
+    }
+}
+
+// rotation of the plane stored as cosine and sine, the local parameter is the angle
+#[cfg(test)]
+#[derive(Clone, Debug)]
+struct Rotation2 {
+    c: f64,
+    s: f64,
+}
+
+#[cfg(test)]
+impl IntoLocalParameters<f64> for Rotation2 {
+    fn into_local_parameters(self, rhs:Self) -> impl ExactSizeIterator<Item=f64> {
+        std::iter::once((self.c*rhs.s-self.s*rhs.c).atan2(self.c*rhs.c+self.s*rhs.s))
+    }
+}
+
+#[cfg(test)]
+impl TryFromLocalParameters<f64,container_traits::LinearContainerConstructError> for Rotation2 {
+    fn try_take_away<I:Iterator<Item=f64>>(self, iter:& mut I) -> Result<Self,container_traits::LinearContainerConstructError> {
+        let phi=iter.next()
+                    .ok_or(container_traits::LenTooSmallError::new(1,0))?;
+        Ok(Rotation2 { c: self.c*phi.cos()-self.s*phi.sin(), s: self.s*phi.cos()+self.c*phi.sin() })
+    }
+
+    container_traits::try_from_local_parameters_impl!(f64);
+}
+
+#[test]
+fn test_rotation_on_manifold() {
+    use crate::{OptimizationOptionsBuilder, ProblemBuilder};
+    let ps=[[1.0, 0.0], [0.0, 2.0], [-1.0, 1.0]];
+    let rotate = |r: Rotation2| ps.iter()
+                                  .flat_map(|p|[r.c*p[0]-r.s*p[1], r.s*p[0]+r.c*p[1]])
+                                  .collect::<Vec<f64>>();
+    let phi=2.5_f64;
+    let opts = OptimizationOptionsBuilder::default()
+        .max_iter(20)
+        .build()
+        .unwrap();
+    let problem = ProblemBuilder::<f64,_,_,_>::new(&rotate, Rotation2 { c: 1.0, s: 0.0 })
+        .target(rotate(Rotation2 { c: phi.cos(), s: phi.sin() }))
+        .set_weights_to_one()
+        .options(opts)
+        .build().unwrap();
+    let report = problem.solve_on_manifold_report::<container_traits::LinearContainerConstructError>().unwrap();
+    let r = report.solution();
+    assert!((r.s.atan2(r.c) - phi).abs() < 1e-8);
+    // the iterates stay on the unit circle
+    assert!((r.c*r.c+r.s*r.s - 1.0).abs() < 1e-12);
+    assert_eq!(report.termination(), &TerminationReason::StepTolerance);
+}
@@ -0,0 +1,108 @@
+use num_traits::One;
+use container_traits::{AnyParameters, IntoParameters, Len, LinearContainerConstructError as LCCE};
+
+use algebra_traits::Scalar;
+
+use matrix::MatrixDyn;
+use crate::dvec::{adjoint_mat_mul, gram, identity, mat_lin_comb, try_solve_columns};
+use crate::jacobian::adjoint_product_hessian;
+use crate::OptimizationError;
+use crate::report::Evaluations;
+use super::Problem;
+
+impl<F    : Scalar,
+     X    : AnyParameters<F,LCCE>+Clone,
+     Y    : IntoParameters<F>+Clone,
+     Func : Fn(X) -> Y> Problem<F,X,Y,Func> {
+
+    // derivative dx/dy of a solution x with respect to the target y. by the implicit function theorem
+    // applied to the normal equations J^H*W*(y-f(x))=0 it is (J^H*W*J-S)^-1*J^H*W with W=V^H*V, where V
+    // is the whitening followed by diag(weights) and S=sum (W*(y-f(x)))_i*hess(f_i) is obtained by
+    // differences of the derivative. S vanishes for exact fits and for linear functions
+    pub fn sensitivity_with_der(&self, x:X, derivative:impl Fn(X) -> MatrixDyn<F>) -> Result<MatrixDyn<F>, OptimizationError<F,X>> {
+        let wjac=self.weighted_jacobian(derivative(x.clone()));
+        let wres=self.weighted_residual(x.clone(), &Evaluations::default())?;
+        let second_order=adjoint_product_hessian(&|x:X|self.weighted_jacobian(derivative(x)), &x, &wres, self.options.fd().clone());
+        let lhs=mat_lin_comb(F::one(), &gram(&wjac), -F::one(), &second_order);
+        // wjac^H*V
+        let rhs=adjoint_mat_mul(&wjac, &self.weighted_jacobian(identity(self.weights.len())));
+        try_solve_columns(&lhs, &rhs).ok_or(OptimizationError::MatrixNotFullRank(lhs, x))
+    }
+
+    pub fn sensitivity(&self, x:X) -> Result<MatrixDyn<F>, OptimizationError<F,X>> {
+        let evaluations=Evaluations::default();
+        self.sensitivity_with_der(x, |x:X| self.numerical_derivative(x, &evaluations))
+    }
+}
+
+#[test]
+fn test_weighted_line_fit_sensitivity() {
+    use algebra::VectorDyn;
+    use crate::ProblemBuilder;
+    let ts=[0.0, 1.0, 2.0, 3.0, 4.0];
+    let ys=vec![1.1, 2.8, 5.0, 7.2, 8.9];
+    let f = |p: [f64;2]| ts.iter().map(|t|p[0]+p[1]*t).collect::<Vec<f64>>();
+    let solve=|ys:Vec<f64>|{
+        let problem=ProblemBuilder::<f64,_,_,_>::new(&f, [0.0, 0.0])
+            .target(ys)
+            .weights(VectorDyn::from(vec![1.0, 2.0, 1.0, 0.5, 1.0]))
+            .build().unwrap();
+        let p=problem.solve().unwrap();
+        (problem, p)
+    };
+    let (problem, p)=solve(ys.clone());
+    let sensitivity=problem.sensitivity(p).unwrap();
+    // the model is linear, such that the solution changes linearly with the target
+    let delta=1e-3;
+    for k in 0..ts.len() {
+        let mut ysk=ys.clone();
+        ysk[k]+=delta;
+        let pk=solve(ysk).1;
+        for i in 0..2 {
+            assert!(((pk[i] - p[i])/delta - sensitivity[(i,k)]).abs() < 1e-6);
+        }
+    }
+}
+
+#[test]
+fn test_exponential_fit_sensitivity() {
+    use container_traits::FromFn;
+    use algebra::VectorDyn;
+    use crate::{OptimizationOptionsBuilder, ProblemBuilder};
+    // the residuals do not vanish at the solution, such that the second derivatives of the model
+    // change the sensitivity by about 1e-3
+    let ts=[0.0, 0.5, 1.0, 1.5, 2.0];
+    let ys=vec![1.0, 1.9, 2.4, 4.9, 6.8];
+    let weights=[1.0, 2.0, 1.0, 0.5, 1.0];
+    let f = |p: [f64;2]| ts.iter().map(|t|p[0]*(p[1]*t).exp()).collect::<Vec<f64>>();
+    let derivative=|p: [f64;2]| MatrixDyn::from_fn((ts.len(), 2),|(i,j)|
+        if j == 0 { (p[1]*ts[i]).exp() } else { p[0]*ts[i]*(p[1]*ts[i]).exp() });
+    let opts=OptimizationOptionsBuilder::default()
+        .max_iter(100)
+        .target_cost(1e-14)
+        .build()
+        .unwrap();
+    let solve=|ys:Vec<f64>, p0:[f64;2]|{
+        let problem=ProblemBuilder::<f64,_,_,_>::new(&f, p0)
+            .target(ys)
+            .weights(VectorDyn::from(weights.to_vec()))
+            .options(opts)
+            .build().unwrap();
+        let p=problem.solve_with_der(derivative).unwrap();
+        (problem, p)
+    };
+    let (problem, p)=solve(ys.clone(), [1.0, 0.5]);
+    let sensitivity=problem.sensitivity_with_der(p, derivative).unwrap();
+    let delta=1e-4;
+    for k in 0..ts.len() {
+        let shifted=|d:f64|{
+            let mut ysk=ys.clone();
+            ysk[k]+=d;
+            solve(ysk, p).1
+        };
+        let (pp, pm)=(shifted(delta), shifted(-delta));
+        for i in 0..2 {
+            assert!(((pp[i] - pm[i])/(2.0*delta) - sensitivity[(i,k)]).abs() < 1e-6);
+        }
+    }
+}
@@ -0,0 +1,31 @@
+// for problems with many parameters, where every residual depends on only a few of them,
+// the numerical jacobian is estimated with the columns grouped by the sparsity pattern.
+// only the number of function evaluations profits from the sparsity: the jacobian is
+// converted to a dense matrix for the gauss-newton steps, which are solved by a dense qr
+// decomposition. the memory and the time of a step hence grow as for a dense jacobian
+use container_traits::{AnyParameters, IntoParameters, LinearContainerConstructError as LCCE};
+
+use algebra_traits::Scalar;
+
+use container::ContainerSparse;
+use crate::{into_dense, sparse_jacobian, OptimizationError, SolveReport};
+use crate::report::Evaluations;
+use super::Problem;
+
+impl<F    : Scalar,
+     X    : AnyParameters<F,LCCE>+Clone,
+     Y    : IntoParameters<F>+Clone,
+     Func : Fn(X) -> Y> Problem<F,X,Y,Func> {
+
+    // the pattern has the size (#residuals, #parameters). the estimated jacobian is densified
+    pub fn solve_with_sparsity(&self, pattern:&ContainerSparse<(usize,usize),bool>) -> Result<X, OptimizationError<F,X>> {
+        self.solve_with_sparsity_report(pattern)
+            .and_then(|report|self.solution(report))
+    }
+
+    pub fn solve_with_sparsity_report(&self, pattern:&ContainerSparse<(usize,usize),bool>) -> Result<SolveReport<F::RealType,X>, OptimizationError<F,X>> {
+        let evaluations=Evaluations::default();
+        self.solve_counted(|x:X|into_dense(&sparse_jacobian(|x:X|self.eval(x, &evaluations), x, pattern, self.options.fd().clone())),
+                           &evaluations)
+    }
+}
@@ -0,0 +1,111 @@
+use num_traits::{One, Zero};
+use container_traits::{AnyParameters, IntoParameters, LinearContainerConstructError as LCCE};
+
+use algebra_traits::{CastFromf64, Norm, Scalar, TryDiv, TrySqrt};
+
+use algebra::VectorDyn;
+
+use matrix::MatrixDyn;
+use crate::dvec::{adjoint_mat_vec, lin_comb, mat_vec, norm2, re_dot, scale};
+use crate::{OptimizationError, SolveReport, TerminationReason, TrustRegionOptions};
+use crate::report::{Evaluations, History};
+use super::{cost, Problem};
+
+// dogleg path from the steepest descent minimizer sd to the gauss-newton step gn
+// cut at the trust region radius, g is the steepest descent direction
+fn dogleg_step<F:Scalar>(gn:VectorDyn<F>, sd:VectorDyn<F>, g:&VectorDyn<F>, radius:F::RealType) -> VectorDyn<F> {
+    let norm=|v:&VectorDyn<F>|v.norm().into_signed();
+    if norm(&gn) <= radius {
+        gn
+    } else if norm(&sd) >= radius {
+        scale(F::from(radius.try_div(norm(g)).unwrap()), g)
+    } else {
+        // solve |sd+tau*(gn-sd)|=radius for tau in [0,1]
+        let d=lin_comb(F::one(), &gn, -F::one(), &sd);
+        let a=norm2(&d);
+        let b=re_dot(&sd, &d);
+        let c=norm2(&sd)-radius.clone()*radius;
+        let disc=(b.clone()*b.clone()-a.clone()*c).try_sqrt().unwrap().into_signed();
+        let tau=(disc-b).try_div(a).unwrap();
+        lin_comb(F::one(), &sd, F::from(tau), &d)
+    }
+}
+
+impl<F    : Scalar,
+     X    : AnyParameters<F,LCCE>+Clone,
+     Y    : IntoParameters<F>+Clone,
+     Func : Fn(X) -> Y> Problem<F,X,Y,Func> {
+
+    pub(super) fn dogleg(&self,
+                         derivative:impl Fn(X) -> MatrixDyn<F>,
+                         tr:TrustRegionOptions<F::RealType>,
+                         evaluations:&Evaluations) -> Result<SolveReport<F::RealType,X>, OptimizationError<F,X>> {
+        let r=F::RealType::from_f64;
+        let opts=&self.options;
+        let mut x=self.first_guess.clone();
+        let mut wjac=self.weighted_jacobian(derivative(x.clone()));
+        let mut wres=self.weighted_residual(x.clone(), evaluations)?;
+        let mut radius=tr.initial_radius().clone();
+        let mut history=History::new(self.observer.as_ref());
+        let mut iter:u8=0;
+        while &iter < opts.max_iter() {
+            if let Some(reason) = self.converged(&wjac, &wres) {
+                return Ok(history.finish(x, reason, evaluations));
+            }
+            let gn=match crate::try_solve_least_squares(wjac.clone(),wres.clone()) {
+                Some(gn) => gn,
+                None => { return Err(OptimizationError::MatrixNotFullRank(wjac, x)); }
+            };
+            if &gn.norm() < opts.target_cost() {
+                return Ok(history.finish(x, TerminationReason::StepTolerance, evaluations));
+            }
+            let g=adjoint_mat_vec(&wjac, &wres);
+            let alpha=norm2(&g).try_div(norm2(&mat_vec(&wjac, &g))).unwrap();
+            let sd=scale(F::from(alpha), &g);
+            let step=dogleg_step(gn, sd, &g, radius.clone());
+            let step_norm=step.norm().into_signed();
+            if &step_norm < opts.target_cost() {
+                return Ok(history.finish(x, TerminationReason::StepTolerance, evaluations));
+            }
+
+            let c0=cost(&wres);
+            let predicted=c0.clone()-cost(&lin_comb(F::one(), &wres, -F::one(), &mat_vec(&wjac, &step)));
+            let xnew=Self::try_update(x.clone(), step)?;
+            let wres_new=self.weighted_residual(xnew.clone(), evaluations)?;
+            let actual=c0.clone()-cost(&wres_new);
+            let rho=actual.clone().try_div(predicted).unwrap();
+            if rho < r(0.25) {
+                radius=radius*r(0.25);
+            } else if rho > r(0.75) && step_norm >= radius.clone()*r(0.99) {
+                radius=radius*r(2.0);
+                if &radius > tr.max_radius() {
+                    radius=tr.max_radius().clone();
+                }
+            }
+            if &rho > tr.eta() && actual > F::RealType::zero() {
+                x=xnew;
+                wres=wres_new;
+                wjac=self.weighted_jacobian(derivative(x.clone()));
+            }
+            history.record(&x, c0, step_norm);
+            iter+=1;
+        }
+        Ok(history.finish(x, TerminationReason::MaxIterations, evaluations))
+    }
+}
+
+#[test]
+fn test_dogleg() {
+    use algebra::Vector2;
+    use crate::{OptimizationOptionsBuilder, StepControl};
+    // rosenbrock function as least squares problem
+    let f = |x: Vector2<f64>| Vector2::from([10.0*(x[1]-x[0]*x[0]), 1.0-x[0]]);
+    let opts = OptimizationOptionsBuilder::default()
+        .step_control(StepControl::TrustRegion(TrustRegionOptions::default()))
+        .max_iter(100)
+        .build()
+        .unwrap();
+    let xsol = crate::fsolve(f, Vector2::from([-1.2, 1.0]), Some(opts)).unwrap();
+    assert!((xsol[0] - 1.0).abs() < 1e-6);
+    assert!((xsol[1] - 1.0).abs() < 1e-6);
+}
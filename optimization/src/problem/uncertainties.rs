@@ -0,0 +1,53 @@
+use container_traits::{AnyParameters, IntoParameters, LinearContainerConstructError as LCCE};
+
+use algebra_traits::Scalar;
+
+use matrix::MatrixDyn;
+use crate::{uncertainties_from_jacobian, OptimizationError, Uncertainties};
+use crate::report::Evaluations;
+use super::Problem;
+
+impl<F    : Scalar,
+     X    : AnyParameters<F,LCCE>+Clone,
+     Y    : IntoParameters<F>+Clone,
+     Func : Fn(X) -> Y> Problem<F,X,Y,Func> {
+
+    // uncertainties of a solution x of the problem, the weights enter the covariance (J^T*W*J)^-1
+    // as W=diag(weights^2), i.e. the weights are the inverse standard deviations of the targets.
+    // with a whitening, W also contains the inverse of its covariance
+    pub fn uncertainties_with_der(&self, x:X, derivative:impl Fn(X) -> MatrixDyn<F>) -> Result<Option<Uncertainties<F>>, OptimizationError<F,X>> {
+        let wjac=self.weighted_jacobian(derivative(x.clone()));
+        let wres=self.weighted_residual(x, &Evaluations::default())?;
+        Ok(uncertainties_from_jacobian(wjac, wres))
+    }
+
+    pub fn uncertainties(&self, x:X) -> Result<Option<Uncertainties<F>>, OptimizationError<F,X>> {
+        let evaluations=Evaluations::default();
+        self.uncertainties_with_der(x, |x:X| self.numerical_derivative(x, &evaluations))
+    }
+}
+
+#[test]
+fn test_line_fit_uncertainties() {
+    use crate::ProblemBuilder;
+    // residuals of the exact fit 1+2t are orthogonal to the columns of the jacobian
+    let ts=[0.0, 1.0, 2.0, 3.0, 4.0];
+    let es=[0.1, -0.2, 0.0, 0.2, -0.1];
+    let ys:Vec<f64>=ts.iter().zip(es.iter()).map(|(t,e)|1.0+2.0*t+e).collect();
+    let f = |p: [f64;2]| ts.iter().map(|t|p[0]+p[1]*t).collect::<Vec<f64>>();
+    let problem = ProblemBuilder::<f64,_,_,_>::new(&f, [0.0, 0.0])
+        .target(ys)
+        .set_weights_to_one()
+        .build().unwrap();
+    let p = problem.solve().unwrap();
+    assert!((p[0] - 1.0).abs() < 1e-8);
+    assert!((p[1] - 2.0).abs() < 1e-8);
+    let unc = problem.uncertainties(p).unwrap().unwrap();
+    // (A^T*A)^-1=[[0.6,-0.2],[-0.2,0.1]] and variance 0.1/3
+    let sds = unc.standard_deviations();
+    assert!((sds[0].clone().into_signed() - 0.02_f64.sqrt()).abs() < 1e-6);
+    assert!((sds[1].clone().into_signed() - (1.0/300.0_f64).sqrt()).abs() < 1e-6);
+    let correlations = unc.correlations().as_ref().unwrap();
+    assert!((correlations[(0,1)] + 0.2/0.06_f64.sqrt()).abs() < 1e-6);
+    assert!((correlations[(0,0)] - 1.0).abs() < 1e-9);
+}
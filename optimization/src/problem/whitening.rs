@@ -0,0 +1,107 @@
+// correlated noise of the residuals, given by a hermitian positive definite covariance matrix c or
+// information matrix c^-1. the residuals and the jacobian are multiplied by a whitening matrix w
+// with w^H*w=c^-1 before the diagonal weights are applied, such that r^H*c^-1*r is minimized.
+// w is computed from the cholesky factorization c=l*l^H (w=l^-1) or c^-1=l*l^H (w=l^H)
+use num_traits::{One, Zero};
+use container_traits::FromFn;
+
+use algebra_traits::Scalar;
+
+use algebra::VectorDyn;
+
+use matrix::MatrixDyn;
+use matrix_traits::MatrixView;
+use matrix_decompositions::Cholesky;
+use crate::dvec::{adjoint, identity, mat_mul, mat_vec, try_solve_columns};
+
+#[derive(Clone, Debug)]
+pub struct Whitening<F> {
+    matrix: MatrixDyn<F>,
+}
+
+impl<F:Scalar> Whitening<F> {
+    // None if the covariance is not positive definite
+    pub fn try_from_covariance(covariance:impl Into<MatrixDyn<F>>) -> Option<Self> {
+        let covariance:MatrixDyn<F>=covariance.into();
+        covariance.try_cholesky()
+                  .and_then(|l|try_solve_columns(&l, &identity(l.nrows())))
+                  .map(|matrix|Whitening{matrix})
+    }
+
+    // None if the information matrix is not positive definite
+    pub fn try_from_information(information:impl Into<MatrixDyn<F>>) -> Option<Self> {
+        let information:MatrixDyn<F>=information.into();
+        information.try_cholesky().map(|l|Whitening{matrix:adjoint(&l)})
+    }
+
+    pub fn matrix(&self) -> &MatrixDyn<F> {
+        &self.matrix
+    }
+
+    // whitening of the concatenation of n uncorrelated residuals with unit variance and the whitened ones
+    pub(super) fn prepend_identity(&self, n:usize) -> Self {
+        let m=self.matrix.nrows();
+        Whitening{matrix:MatrixDyn::from_fn((n+m, n+m),|(i,j)|
+            if i < n || j < n {
+                if i == j { F::one() } else { F::zero() }
+            } else {
+                self.matrix[(i-n,j-n)].clone()
+            })}
+    }
+
+    pub(super) fn whiten(&self, res:&VectorDyn<F>) -> VectorDyn<F> {
+        mat_vec(&self.matrix, res)
+    }
+
+    pub(super) fn whiten_jacobian(&self, jac:MatrixDyn<F>) -> MatrixDyn<F> {
+        mat_mul(&self.matrix, &jac)
+    }
+}
+
+#[test]
+fn test_correlated_line_fit() {
+    use container_traits::NewUnchecked;
+    use matrix::{SquareMatrixDyn, SymmetricMatrixDyn};
+    use crate::ProblemBuilder;
+    let ts=[0.0, 1.0, 2.0];
+    let ys=vec![1.0, 3.5, 4.8];
+    let c=[[2.0, 1.0, 0.0], [1.0, 2.0, 1.0], [0.0, 1.0, 2.0]];
+    // the inverse of c
+    let info=[[0.75, -0.5, 0.25], [-0.5, 1.0, -0.5], [0.25, -0.5, 0.75]];
+    let covariance=SymmetricMatrixDyn::new_unchecked(
+        SquareMatrixDyn::new_unchecked(MatrixDyn::from_fn((3,3),|(i,j)|c[i][j])));
+    let f = |p: [f64;2]| ts.iter().map(|t|p[0]+p[1]*t).collect::<Vec<f64>>();
+    let solve=|whitening:Whitening<f64>|ProblemBuilder::<f64,_,_,_>::new(&f, [0.0, 0.0])
+        .target(ys.clone())
+        .set_weights_to_one()
+        .whitening(whitening)
+        .build().unwrap()
+        .solve().unwrap();
+    let whitening=Whitening::try_from_covariance(covariance).unwrap();
+    let w=whitening.matrix();
+    for i in 0..3 {
+        for j in 0..3 {
+            let wtw:f64=(0..3).map(|k|w[(k,i)]*w[(k,j)]).sum();
+            assert!((wtw - info[i][j]).abs() < 1e-12);
+        }
+    }
+    let p=solve(whitening);
+    // generalized least squares: the residual is orthogonal to the columns of the jacobian in the metric c^-1
+    let res:Vec<f64>=(0..3).map(|i|ys[i] - p[0] - p[1]*ts[i]).collect();
+    let lres:Vec<f64>=(0..3).map(|i|(0..3).map(|j|info[i][j]*res[j]).sum()).collect();
+    assert!(lres.iter().sum::<f64>().abs() < 1e-8);
+    assert!(lres.iter().zip(ts.iter()).map(|(r,t)|r*t).sum::<f64>().abs() < 1e-8);
+    let q=solve(Whitening::try_from_information(MatrixDyn::from_fn((3,3),|(i,j)|info[i][j])).unwrap());
+    assert!((p[0] - q[0]).abs() < 1e-8);
+    assert!((p[1] - q[1]).abs() < 1e-8);
+    let indefinite=MatrixDyn::from_fn((2,2),|(i,j)|if i == j { 1.0 } else { 2.0 });
+    assert!(Whitening::try_from_covariance(indefinite).is_none());
+    // the whitening has to act on all three residuals
+    let identity=MatrixDyn::from_fn((2,2),|(i,j)|if i == j { 1.0 } else { 0.0 });
+    assert!(ProblemBuilder::<f64,_,_,_>::new(&f, [0.0, 0.0])
+        .target(ys.clone())
+        .set_weights_to_one()
+        .whitening(Whitening::try_from_information(identity).unwrap())
+        .build()
+        .is_err());
+}
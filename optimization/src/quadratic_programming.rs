@@ -0,0 +1,209 @@
+// dense convex quadratic programs
+// min x^T*h*x/2+g^T*x subject to a*x=b and c*x<=d
+// solved by the dual active set method of goldfarb and idnani. starting from the minimizer subject
+// to the equality constraints, the most violated inequality is added to the active set while the
+// multipliers of the active constraints stay nonnegative. the kkt systems are solved by qr,
+// h has to be positive definite on the null space of the active constraints
+use num_traits::{One, Zero};
+use container_traits::{FromFn, Iter, Len, LenNotEqualToRequiredLenError};
+
+use algebra_traits::{CastFromf64, RealNumber, TryDiv};
+
+use algebra::VectorDyn;
+
+use matrix::MatrixDyn;
+use matrix_traits::MatrixView;
+use crate::dvec::{dot, kkt_matrix, lin_comb, scale};
+use super::OptimizationError;
+
+// the multipliers mu of the lagrangian x^T*h*x/2+g^T*x+mu_a^T*(a*x-b)+mu_c^T*(c*x-d),
+// the ones of the inactive inequalities vanish
+#[derive(Clone, Debug, derive_getters::Getters)]
+pub struct QpSolution<F> {
+    x: VectorDyn<F>,
+    equality_multipliers: VectorDyn<F>,
+    inequality_multipliers: VectorDyn<F>,
+    active: Vec<usize>,
+}
+
+impl<F> QpSolution<F> {
+    pub fn into_x(self) -> VectorDyn<F> {
+        self.x
+    }
+}
+
+fn rows<F:RealNumber>(m:&MatrixDyn<F>) -> Vec<VectorDyn<F>> {
+    (0..m.nrows()).map(|i|VectorDyn::from_iter((0..m.ncols()).map(|j|m[(i,j)].clone())))
+                  .collect()
+}
+
+// solves [h, w^T; w, 0]*[x; mu]=[rhs; wrhs] where w has the given rows,
+// the kkt matrix is returned if it is singular
+fn solve_kkt<F:RealNumber>(h:&MatrixDyn<F>,
+                           w:&[&VectorDyn<F>],
+                           rhs:&VectorDyn<F>,
+                           wrhs:impl Iterator<Item=F>) -> Result<(VectorDyn<F>, Vec<F>), MatrixDyn<F>> {
+    let n=h.nrows();
+    let kkt=kkt_matrix(h, &MatrixDyn::from_fn((w.len(), n),|(i,j)|w[i][j].clone()));
+    let rhs=VectorDyn::from_iter(rhs.iter().cloned().chain(wrhs));
+    match crate::try_solve_least_squares(kkt.clone(), rhs) {
+        Some(sol) => Ok((VectorDyn::from_iter(sol.iter().take(n).cloned()),
+                         sol.iter().skip(n).cloned().collect())),
+        None      => Err(kkt),
+    }
+}
+
+pub fn quadratic_program<F:RealNumber>(
+    h:impl Into<MatrixDyn<F>>,
+    g:VectorDyn<F>,
+    a:MatrixDyn<F>,
+    b:VectorDyn<F>,
+    c:MatrixDyn<F>,
+    d:VectorDyn<F>,
+    maxiter:Option<u8>) -> Result<QpSolution<F>, OptimizationError<F,VectorDyn<F>>> {
+    let h:MatrixDyn<F>=h.into();
+    let n=h.nrows();
+    for len in [h.ncols(), g.len(), a.ncols(), c.ncols()] {
+        if len != n {
+            return Err(OptimizationError::ContainerConstruct(LenNotEqualToRequiredLenError::new(n, len).into()));
+        }
+    }
+    for (m, rhs) in [(&a, &b), (&c, &d)] {
+        if rhs.len() != m.nrows() {
+            return Err(OptimizationError::ContainerConstruct(LenNotEqualToRequiredLenError::new(m.nrows(), rhs.len()).into()));
+        }
+    }
+    let maxiter=maxiter.unwrap_or(100);
+    let tol=F::from_f64(1e-10);
+    let a_rows=rows(&a);
+    let c_rows=rows(&c);
+    let neq=a_rows.len();
+    let working=|active:&[usize]|a_rows.iter()
+                                        .chain(active.iter().map(|k|&c_rows[*k]))
+                                        .collect::<Vec<_>>();
+    let neg_g=scale(-F::one(), &g);
+    let (mut x, mut mu)=solve_kkt(&h, &working(&[]), &neg_g, b.iter().cloned())
+        .map_err(|kkt|OptimizationError::MatrixNotFullRank(kkt, VectorDyn::from_iter((0..n).map(|_|F::zero()))))?;
+    let mut active:Vec<usize>=Vec::new();
+    let mut iter=0;
+    while iter < maxiter {
+        // the most violated inequality
+        let violated=(0..c_rows.len())
+            .filter(|k|!active.contains(k))
+            .map(|k|(k, dot(&c_rows[k], &x)-d[k].clone()))
+            .fold(None,|acc:Option<(usize,F)>,(k,v)|match acc {
+                Some((_, vmax)) if vmax >= v => acc,
+                _                            => Some((k, v)),
+            });
+        let p=match violated {
+            Some((p, v)) if v > tol => p,
+            _ => {
+                let mut inequality_multipliers=VectorDyn::from_iter((0..c_rows.len()).map(|_|F::zero()));
+                for (k, muk) in active.iter().zip(mu.iter().skip(neq)) {
+                    inequality_multipliers[*k]=muk.clone();
+                }
+                return Ok(QpSolution{x,
+                                     equality_multipliers:VectorDyn::from_iter(mu.into_iter().take(neq)),
+                                     inequality_multipliers,
+                                     active});
+            },
+        };
+        // the multiplier t of the constraint p is increased, the multipliers of the active
+        // constraints change by t*dmu. constraints whose multipliers vanish are dropped
+        let neg_cp=scale(-F::one(), &c_rows[p]);
+        let mut t=F::zero();
+        while iter < maxiter {
+            iter+=1;
+            let (dx, dmu)=solve_kkt(&h, &working(&active), &neg_cp, mu.iter().map(|_|F::zero()))
+                .map_err(|kkt|OptimizationError::MatrixNotFullRank(kkt, x.clone()))?;
+            let slope=dot(&c_rows[p], &dx);
+            // step to the boundary of the constraint p
+            // a step which is not representable means that p can not be added to the active set
+            let infeasible=|_|OptimizationError::<F,VectorDyn<F>>::InfeasibleConstraint(p);
+            let full=if slope < -tol.clone() {
+                Some((dot(&c_rows[p], &x)-d[p].clone()).try_div(-slope).map_err(infeasible)?)
+            } else {
+                None
+            };
+            // step until a multiplier of an active inequality vanishes
+            let partial=(neq..mu.len())
+                .filter(|i|dmu[*i] < F::zero())
+                .map(|i|mu[i].clone().try_div(-dmu[i].clone()).map(|ti|(i, ti)).map_err(infeasible))
+                .collect::<Result<Vec<(usize,F)>,_>>()?
+                .into_iter()
+                .fold(None,|acc:Option<(usize,F)>,(i,ti)|match acc {
+                    Some((_, tmin)) if tmin <= ti => acc,
+                    _                             => Some((i, ti)),
+                });
+            let (step, drop)=match (full, partial) {
+                (None,        None                            ) => { return Err(OptimizationError::InfeasibleConstraint(p)); },
+                (Some(tf),    Some((i, tp))) if tp < tf         => (tp, Some(i)),
+                (Some(tf),    _                               ) => (tf, None),
+                (None,        Some((i, tp))                   ) => (tp, Some(i)),
+            };
+            x=lin_comb(F::one(), &x, step.clone(), &dx);
+            mu=mu.iter()
+                 .zip(dmu.iter())
+                 .map(|(mi,dmi)|mi.clone()+step.clone()*dmi.clone())
+                 .collect();
+            t=t+step;
+            match drop {
+                Some(i) => {
+                    active.remove(i-neq);
+                    mu.remove(i);
+                },
+                None => {
+                    active.push(p);
+                    mu.push(t);
+                    break;
+                },
+            }
+        }
+    }
+    Err(OptimizationError::MaximalIteration(iter))
+}
+
+#[cfg(test)]
+fn example_constraints() -> (MatrixDyn<f64>, VectorDyn<f64>) {
+    let c=[[-1.0, 2.0], [1.0, 2.0], [1.0, -2.0], [-1.0, 0.0], [0.0, -1.0]];
+    (MatrixDyn::from_fn((5,2),|(i,j)|c[i][j]), VectorDyn::from_iter([2.0, 6.0, 2.0, 0.0, 0.0]))
+}
+
+#[test]
+fn test_qp_inequalities() {
+    // min (x0-1)^2+(x1-2.5)^2, the first constraint is active
+    let h=MatrixDyn::from_fn((2,2),|(i,j)|if i == j { 2.0 } else { 0.0 });
+    let (c, d)=example_constraints();
+    let no_rows=MatrixDyn::from_fn((0,2),|_|0.0);
+    let sol=quadratic_program(h, VectorDyn::from_iter([-2.0, -5.0]), no_rows, VectorDyn::from_iter([]), c, d, None).unwrap();
+    assert!((sol.x()[0] - 1.4).abs() < 1e-10);
+    assert!((sol.x()[1] - 1.7).abs() < 1e-10);
+    assert_eq!(sol.active(), &vec![0]);
+    assert!((sol.inequality_multipliers()[0] - 0.8).abs() < 1e-10);
+    assert_eq!(sol.inequality_multipliers()[1], 0.0);
+}
+
+#[test]
+fn test_qp_symmetric_hessian_with_equality() {
+    use container_traits::NewUnchecked;
+    use matrix::{SquareMatrixDyn, SymmetricMatrixDyn};
+    let h=SymmetricMatrixDyn::new_unchecked(
+        SquareMatrixDyn::new_unchecked(MatrixDyn::from_fn((2,2),|(i,j)|if i == j { 2.0 } else { 0.0 })));
+    let (c, d)=example_constraints();
+    let a=MatrixDyn::from_fn((1,2),|_|1.0);
+    let sol=quadratic_program(h, VectorDyn::from_iter([-2.0, -5.0]), a, VectorDyn::from_iter([3.0]), c, d, None).unwrap();
+    assert!((sol.x()[0] - 4.0/3.0).abs() < 1e-10);
+    assert!((sol.x()[1] - 5.0/3.0).abs() < 1e-10);
+    assert!((sol.equality_multipliers()[0] - 1.0/9.0).abs() < 1e-10);
+    assert!((sol.inequality_multipliers()[0] - 7.0/9.0).abs() < 1e-10);
+}
+
+#[test]
+fn test_qp_infeasible() {
+    // x0 <= -1 and -x0 <= -1
+    let h=MatrixDyn::from_fn((1,1),|_|1.0);
+    let c=MatrixDyn::from_fn((2,1),|(i,_)|if i == 0 { 1.0 } else { -1.0 });
+    let no_rows=MatrixDyn::from_fn((0,1),|_|0.0);
+    let res=quadratic_program(h, VectorDyn::from_iter([0.0]), no_rows, VectorDyn::from_iter([]), c, VectorDyn::from_iter([-1.0, -1.0]), None);
+    assert!(matches!(res, Err(OptimizationError::InfeasibleConstraint(_))));
+}
@@ -0,0 +1,239 @@
+// minimization of a smooth function by quasi-newton methods, the inverse of the hessian
+// is approximated by updates with the changes of the gradient along the steps.
+// the parameters and the values are real numbers: the curvature conditions and the wolfe
+// conditions compare inner products, which are complex for complex scalars. complex
+// parameters have to be passed as pairs of real and imaginary parts
+
+mod wolfe;
+
+use std::collections::VecDeque;
+use num_traits::{One, Zero};
+use container_traits::{AnyParameters, FromFn, IntoParameters, Len, LinearContainerConstructError as LCCE};
+
+use algebra_traits::{Norm, RealNumber, TryDiv};
+
+use algebra::VectorDyn;
+
+use matrix::MatrixDyn;
+use crate::dvec::{dot, lin_comb, mat_vec, scale};
+use crate::report::{Evaluations, History};
+use super::{from_dvec, into_dvec, FiniteDifference, OptimizationError, QuasiNewtonMethod, QuasiNewtonOptions, SolveReport, TerminationReason};
+use wolfe::{wolfe_line_search, Point};
+
+enum InverseHessian<F> {
+    Dense(MatrixDyn<F>),
+    // pairs of steps and gradient changes, the most recent one last
+    Limited(VecDeque<(VectorDyn<F>, VectorDyn<F>)>, usize),
+}
+
+impl<F:RealNumber> InverseHessian<F> {
+    fn new(method:&QuasiNewtonMethod, n:usize) -> Self {
+        match method {
+            QuasiNewtonMethod::Bfgs                 => InverseHessian::Dense(Self::scaled_identity(n, F::one())),
+            QuasiNewtonMethod::LimitedMemoryBfgs(m) => InverseHessian::Limited(VecDeque::new(), *m),
+        }
+    }
+
+    fn scaled_identity(n:usize, gamma:F) -> MatrixDyn<F> {
+        MatrixDyn::from_fn((n,n),|(i,j)|if i == j { gamma.clone() } else { F::zero() })
+    }
+
+    fn reset(&mut self, n:usize) {
+        match self {
+            InverseHessian::Dense(h) => { *h=Self::scaled_identity(n, F::one()); },
+            InverseHessian::Limited(pairs, _) => { pairs.clear(); },
+        }
+    }
+
+    // -H*g
+    fn direction(&self, g:&VectorDyn<F>) -> VectorDyn<F> {
+        match self {
+            InverseHessian::Dense(h) => scale(-F::one(), &mat_vec(h, g)),
+            InverseHessian::Limited(pairs, _) => {
+                // two loop recursion
+                let mut q=g.clone();
+                let mut alphas=Vec::new();
+                for (s,y) in pairs.iter().rev() {
+                    let alpha=dot(s, &q).try_div(dot(y, s)).unwrap();
+                    q=lin_comb(F::one(), &q, -alpha.clone(), y);
+                    alphas.push(alpha);
+                }
+                let gamma=match pairs.back() {
+                    Some((s,y)) => dot(s, y).try_div(dot(y, y)).unwrap(),
+                    None => F::one(),
+                };
+                let mut r=scale(gamma, &q);
+                for ((s,y),alpha) in pairs.iter().zip(alphas.into_iter().rev()) {
+                    let beta=dot(y, &r).try_div(dot(y, s)).unwrap();
+                    r=lin_comb(F::one(), &r, alpha-beta, s);
+                }
+                scale(-F::one(), &r)
+            },
+        }
+    }
+
+    // requires s.y > 0, the initial dense approximation is scaled by s.y/y.y before the first update
+    fn update(&mut self, s:VectorDyn<F>, y:VectorDyn<F>, first:bool) {
+        match self {
+            InverseHessian::Dense(h) => {
+                let sy=dot(&s, &y);
+                if first {
+                    *h=Self::scaled_identity(s.len(), sy.clone().try_div(dot(&y, &y)).unwrap());
+                }
+                let rho=F::one().try_div(sy).unwrap();
+                let hy=mat_vec(h, &y);
+                let yhy=dot(&y, &hy);
+                let c=rho.clone()*rho.clone()*yhy+rho.clone();
+                // (I-rho*s*y^T)*H*(I-rho*y*s^T)+rho*s*s^T
+                *h=MatrixDyn::from_fn((s.len(),s.len()),|(i,j)|
+                    h[(i,j)].clone()-rho.clone()*(s[i].clone()*hy[j].clone()+hy[i].clone()*s[j].clone())
+                                    +c.clone()*s[i].clone()*s[j].clone());
+            },
+            InverseHessian::Limited(pairs, m) => {
+                pairs.push_back((s, y));
+                if pairs.len() > *m {
+                    pairs.pop_front();
+                }
+            },
+        }
+    }
+}
+
+fn minimize<F : RealNumber,
+            X : AnyParameters<F,LCCE>+Clone>(
+    f:impl Fn(&VectorDyn<F>) -> F,
+    gradient:impl Fn(&VectorDyn<F>, &F) -> VectorDyn<F>,
+    x0:VectorDyn<F>,
+    opts:QuasiNewtonOptions<F>,
+    evaluations:&Evaluations) -> Result<SolveReport<F,X>, OptimizationError<F,X>> {
+    let n=x0.len();
+    let fx0=f(&x0);
+    let g0=gradient(&x0, &fx0);
+    let mut point=Point { x: x0, fx: fx0, g: g0 };
+    let mut hinv=InverseHessian::new(opts.method(), n);
+    let mut first=true;
+    let mut history=History::new(None);
+    let mut iter:u8=0;
+    while &iter < opts.max_iter() {
+        if &point.g.norm() < opts.gradient_tolerance() {
+            return Ok(history.finish(from_dvec(point.x), TerminationReason::GradientTolerance, evaluations));
+        }
+        let mut p=hinv.direction(&point.g);
+        if dot(&p, &point.g) >= F::zero() {
+            // the approximation lost positive definiteness, restart with steepest descent
+            hinv.reset(n);
+            first=true;
+            p=scale(-F::one(), &point.g);
+        }
+        let next=match wolfe_line_search(&f, &gradient, &point, &p, opts.wolfe()) {
+            Some(next) => next,
+            None => { return Ok(history.finish(from_dvec(point.x), TerminationReason::StepTolerance, evaluations)); }
+        };
+        let s=lin_comb(F::one(), &next.x, -F::one(), &point.x);
+        let y=lin_comb(F::one(), &next.g, -F::one(), &point.g);
+        let step_norm=s.norm().into_signed();
+        if dot(&s, &y) > F::zero() {
+            hinv.update(s, y, first);
+            first=false;
+        }
+        let cost=point.fx.clone();
+        point=next;
+        history.record(&from_dvec::<F,X>(point.x.clone()), cost, step_norm.clone());
+        if &step_norm < opts.step_tolerance() {
+            return Ok(history.finish(from_dvec(point.x), TerminationReason::StepTolerance, evaluations));
+        }
+        iter+=1;
+    }
+    Ok(history.finish(from_dvec(point.x), TerminationReason::MaxIterations, evaluations))
+}
+
+pub(crate) fn numerical_gradient<F:RealNumber>(f:impl Fn(&VectorDyn<F>) -> F, x:&VectorDyn<F>, fx:&F, fd:&FiniteDifference<F>) -> VectorDyn<F> {
+    VectorDyn::from_iter(
+        (0..x.len()).map(|i|fd.apply(|h:F|{
+            let mut xh=x.clone();
+            xh[i]+=h;
+            f(&xh)
+        }, fx, &x[i])))
+}
+
+// the gradient is computed with the finite difference of the options.
+// restricted to real parameters F:RealNumber, unlike the least squares solvers of Problem
+pub fn quasi_newton<F : RealNumber,
+                    X : AnyParameters<F,LCCE>+Clone>(
+    f:impl Fn(X) -> F,
+    x0:X,
+    opts:Option<QuasiNewtonOptions<F>>) -> Result<SolveReport<F,X>, OptimizationError<F,X>> {
+    let opts=opts.unwrap_or_default();
+    let evaluations=Evaluations::default();
+    let f=|x:&VectorDyn<F>|{
+        evaluations.count_function();
+        f(from_dvec(x.clone()))
+    };
+    let gradient=|x:&VectorDyn<F>, fx:&F|{
+        evaluations.count_jacobian();
+        numerical_gradient(&f, x, fx, opts.fd())
+    };
+    minimize(&f, gradient, into_dvec(x0), opts.clone(), &evaluations)
+}
+
+// restricted to real parameters F:RealNumber, the gradient has the same components as X
+pub fn quasi_newton_with_gradient<F : RealNumber,
+                                  X : AnyParameters<F,LCCE>+Clone,
+                                  G : IntoParameters<F>>(
+    f:impl Fn(X) -> F,
+    gradient:impl Fn(X) -> G,
+    x0:X,
+    opts:Option<QuasiNewtonOptions<F>>) -> Result<SolveReport<F,X>, OptimizationError<F,X>> {
+    let evaluations=Evaluations::default();
+    let f=|x:&VectorDyn<F>|{
+        evaluations.count_function();
+        f(from_dvec(x.clone()))
+    };
+    let gradient=|x:&VectorDyn<F>, _:&F|{
+        evaluations.count_jacobian();
+        into_dvec(gradient(from_dvec(x.clone())))
+    };
+    minimize(f, gradient, into_dvec(x0), opts.unwrap_or_default(), &evaluations)
+}
+
+#[cfg(test)]
+fn rosenbrock(x:[f64;2]) -> f64 {
+    (1.0-x[0]).powi(2)+100.0*(x[1]-x[0]*x[0]).powi(2)
+}
+
+#[cfg(test)]
+fn rosenbrock_gradient(x:[f64;2]) -> [f64;2] {
+    [-2.0*(1.0-x[0])-400.0*x[0]*(x[1]-x[0]*x[0]), 200.0*(x[1]-x[0]*x[0])]
+}
+
+#[test]
+fn test_bfgs_and_lbfgs_rosenbrock() {
+    use crate::QuasiNewtonOptionsBuilder;
+    for method in [QuasiNewtonMethod::Bfgs, QuasiNewtonMethod::LimitedMemoryBfgs(5)] {
+        let opts = QuasiNewtonOptionsBuilder::default()
+            .method(method)
+            .gradient_tolerance(1e-10)
+            .max_iter(200)
+            .build()
+            .unwrap();
+        let report = quasi_newton_with_gradient(rosenbrock, rosenbrock_gradient, [-1.2, 1.0], Some(opts)).unwrap();
+        assert_eq!(report.termination(), &TerminationReason::GradientTolerance);
+        assert!((report.solution()[0] - 1.0).abs() < 1e-8);
+        assert!((report.solution()[1] - 1.0).abs() < 1e-8);
+        assert!(report.history()
+                      .windows(2)
+                      .all(|w|w[1].cost() <= w[0].cost()));
+    }
+}
+
+#[test]
+fn test_bfgs_numerical_gradient() {
+    let f = |x: [f64;3]| (x[0]-1.0).powi(2)+2.0*(x[1]+2.0).powi(2)+3.0*(x[2]-0.5).powi(2)+x[0]*x[1];
+    let report = quasi_newton(f, [0.0, 0.0, 0.0], None).unwrap();
+    // stationary point of the quadratic: 2(x0-1)+x1=0, 4(x1+2)+x0=0
+    let x = report.solution();
+    assert!((x[0] - 16.0/7.0).abs() < 1e-5);
+    assert!((x[1] + 18.0/7.0).abs() < 1e-5);
+    assert!((x[2] - 0.5).abs() < 1e-5);
+    assert!(*report.function_evaluations() > *report.jacobian_evaluations());
+}
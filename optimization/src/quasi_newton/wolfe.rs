@@ -0,0 +1,77 @@
+use num_traits::{One, Zero};
+use algebra_traits::{CastFromf64, Norm, RealNumber};
+
+use algebra::VectorDyn;
+
+use crate::dvec::{dot, lin_comb};
+use crate::WolfeOptions;
+
+#[derive(Clone, Debug)]
+pub(super) struct Point<F> {
+    pub(super) x: VectorDyn<F>,
+    pub(super) fx: F,
+    pub(super) g: VectorDyn<F>,
+}
+
+// line search along the descent direction p, the step length is doubled until
+// an interval containing acceptable step lengths is found, which is then bisected.
+// returns None if no point satisfying the strong wolfe conditions is found
+pub(super) fn wolfe_line_search<F:RealNumber>(f:impl Fn(&VectorDyn<F>) -> F,
+                                              gradient:impl Fn(&VectorDyn<F>, &F) -> VectorDyn<F>,
+                                              start:&Point<F>,
+                                              p:&VectorDyn<F>,
+                                              opts:&WolfeOptions<F>) -> Option<Point<F>> {
+    let f0=start.fx.clone();
+    let slope0=dot(&start.g, p);
+    let sufficient_decrease=|t:&F, ft:&F|ft <= &(f0.clone()+opts.c1().clone()*t.clone()*slope0.clone());
+    let curvature=|slope:&F|slope.norm().into_signed() <= -opts.c2().clone()*slope0.clone();
+    let mut t_prev=F::zero();
+    let mut f_prev=f0.clone();
+    let mut t_next=F::one();
+    // (t_lo, f(t_lo), t_hi) where t_lo satisfies the sufficient decrease condition
+    let mut bracket:Option<(F,F,F)>=None;
+    let mut evaluations=0;
+    while &evaluations < opts.max_evaluations() {
+        let t=match &bracket {
+            None => t_next.clone(),
+            Some((lo,_,hi)) => (lo.clone()+hi.clone())*F::from_f64(0.5),
+        };
+        let xt=lin_comb(F::one(), &start.x, t.clone(), p);
+        let ft=f(&xt);
+        evaluations+=1;
+        match bracket.clone() {
+            None => {
+                if !sufficient_decrease(&t, &ft) || (t_prev > F::zero() && ft >= f_prev) {
+                    bracket=Some((t_prev.clone(), f_prev.clone(), t));
+                    continue;
+                }
+                let gt=gradient(&xt, &ft);
+                let slope=dot(&gt, p);
+                if curvature(&slope) {
+                    return Some(Point { x: xt, fx: ft, g: gt });
+                }
+                if slope >= F::zero() {
+                    bracket=Some((t, ft, t_prev.clone()));
+                    continue;
+                }
+                t_prev=t.clone();
+                f_prev=ft;
+                t_next=t*F::from_f64(2.0);
+            },
+            Some((lo, flo, hi)) => {
+                if !sufficient_decrease(&t, &ft) || ft >= flo {
+                    bracket=Some((lo, flo, t));
+                    continue;
+                }
+                let gt=gradient(&xt, &ft);
+                let slope=dot(&gt, p);
+                if curvature(&slope) {
+                    return Some(Point { x: xt, fx: ft, g: gt });
+                }
+                let hi=if slope.clone()*(hi.clone()-lo.clone()) >= F::zero() { lo } else { hi };
+                bracket=Some((t, ft, hi));
+            },
+        }
+    }
+    None
+}
@@ -0,0 +1,200 @@
+use std::cell::Cell;
+use std::sync::Arc;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TerminationReason {
+    CostTolerance,
+    StepTolerance,
+    GradientTolerance,
+    MaxIterations,
+}
+
+// cost is the weighted sum of squares at the start of the iteration,
+// step_norm the norm of the step taken (or rejected) in the iteration
+#[derive(Clone, Debug, derive_getters::Getters)]
+pub struct Iteration<R> {
+    cost: R,
+    step_norm: R,
+}
+
+#[derive(Clone, Debug, derive_getters::Getters)]
+pub struct SolveReport<R,X> {
+    solution: X,
+    history: Vec<Iteration<R>>,
+    function_evaluations: usize,
+    jacobian_evaluations: usize,
+    // jacobians replaced by secant updates
+    jacobian_evaluations_saved: usize,
+    termination: TerminationReason,
+}
+
+impl<R,X> SolveReport<R,X> {
+    pub fn iterations(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn final_cost(&self) -> Option<&R> {
+        self.history
+            .last()
+            .map(|it|&it.cost)
+    }
+
+    pub fn into_solution(self) -> X {
+        self.solution
+    }
+}
+
+// called after every iteration with the current iterate.
+// it is shared such that problems can be sent to or solved from other threads
+pub struct Observer<R,X>(Arc<dyn Fn(&X, &Iteration<R>)+Send+Sync>);
+
+impl<R,X> Clone for Observer<R,X> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<R,X> Observer<R,X> {
+    pub fn new(f:impl Fn(&X, &Iteration<R>)+Send+Sync+'static) -> Self {
+        Self(Arc::new(f))
+    }
+}
+
+impl<R,X> std::fmt::Debug for Observer<R,X> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Observer")
+    }
+}
+
+// counts of a single solve. every solve creates its own counters and passes them to the evaluations
+// of the function, such that concurrent solves of the same problem do not interfere
+#[derive(Debug, Default)]
+pub(crate) struct Evaluations {
+    function: Cell<usize>,
+    jacobian: Cell<usize>,
+    jacobian_saved: Cell<usize>,
+}
+
+impl Evaluations {
+    pub(crate) fn count_function(&self) {
+        self.function.set(self.function.get()+1);
+    }
+
+    pub(crate) fn count_jacobian(&self) {
+        self.jacobian.set(self.jacobian.get()+1);
+    }
+
+    pub(crate) fn count_saved_jacobian(&self) {
+        self.jacobian_saved.set(self.jacobian_saved.get()+1);
+    }
+}
+
+// collects the iterations of a solver and passes them to the observer
+pub(crate) struct History<'a,R,X> {
+    iterations: Vec<Iteration<R>>,
+    observer: Option<&'a Observer<R,X>>,
+}
+
+impl<'a,R,X> History<'a,R,X> {
+    pub(crate) fn new(observer:Option<&'a Observer<R,X>>) -> Self {
+        Self { iterations: Vec::new(), observer }
+    }
+
+    pub(crate) fn record(&mut self, x:&X, cost:R, step_norm:R) {
+        let it=Iteration { cost, step_norm };
+        if let Some(observer) = self.observer {
+            (observer.0)(x, &it);
+        }
+        self.iterations.push(it);
+    }
+
+    pub(crate) fn finish(self, solution:X, termination:TerminationReason, evaluations:&Evaluations) -> SolveReport<R,X> {
+        SolveReport {
+            solution,
+            history: self.iterations,
+            function_evaluations: evaluations.function.get(),
+            jacobian_evaluations: evaluations.jacobian.get(),
+            jacobian_evaluations_saved: evaluations.jacobian_saved.get(),
+            termination,
+        }
+    }
+}
+
+#[test]
+fn test_report_and_observer() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::ProblemBuilder;
+    let calls=Arc::new(AtomicUsize::new(0));
+    let counter=calls.clone();
+    let f = |x: [f64;2]| [x[0]*x[0] - 2.0, x[1] - 1.0];
+    let report = ProblemBuilder::<f64,_,_,_>::new(&f, [1.0, 0.0])
+        .set_target_to_zero()
+        .set_weights_to_one()
+        .observer(Observer::new(move |_:&[f64;2], _:&Iteration<f64>|{ counter.fetch_add(1, Ordering::Relaxed); }))
+        .build().unwrap()
+        .solve_report().unwrap();
+    assert_eq!(report.termination(), &TerminationReason::StepTolerance);
+    assert_eq!(report.iterations(), calls.load(Ordering::Relaxed));
+    assert!(report.iterations() > 0);
+    // one jacobian per iteration and for the final step test
+    assert_eq!(*report.jacobian_evaluations(), report.iterations()+1);
+    // numerical centered derivatives need 4 evaluations per jacobian
+    assert!(*report.function_evaluations() > 4*report.iterations());
+    assert!(report.history()
+                  .windows(2)
+                  .all(|w|w[1].cost() < w[0].cost()));
+    assert!((report.solution()[0] - 2.0_f64.sqrt()).abs() < 1e-8);
+}
+
+#[test]
+fn test_problem_is_send_and_sync() {
+    use crate::{Problem, ProblemBuilder, RobustLoss};
+    fn assert_send_sync<T:Send+Sync>(_:&T) {}
+    let f = |x: [f64;2]| [x[0] - 1.0, x[1] - 2.0];
+    let problem:Problem<f64,_,_,_> = ProblemBuilder::<f64,_,_,_>::new(&f, [0.0, 0.0])
+        .set_target_to_zero()
+        .set_weights_to_one()
+        .observer(Observer::new(|_:&[f64;2], _:&Iteration<f64>|{}))
+        .robust_loss(RobustLoss::Custom(Arc::new(|u:f64|1.0/(1.0+u))))
+        .build().unwrap();
+    assert_send_sync(&problem);
+    let x=std::thread::scope(|s|s.spawn(||problem.solve().unwrap()).join().unwrap());
+    assert!((x[0] - 1.0).abs() < 1e-8);
+}
+
+#[test]
+fn test_concurrent_solves_count_separately() {
+    use crate::ProblemBuilder;
+    let f = |x: [f64;2]| [x[0]*x[0] - 2.0, x[1] - 1.0];
+    let problem = ProblemBuilder::<f64,_,_,_>::new(&f, [1.0, 0.0])
+        .set_target_to_zero()
+        .set_weights_to_one()
+        .build().unwrap();
+    let single=problem.solve_report().unwrap();
+    let reports=std::thread::scope(|s|{
+        let handles:Vec<_>=(0..4).map(|_|s.spawn(||problem.solve_report().unwrap())).collect();
+        handles.into_iter().map(|h|h.join().unwrap()).collect::<Vec<_>>()
+    });
+    for report in reports {
+        assert_eq!(report.function_evaluations(), single.function_evaluations());
+        assert_eq!(report.jacobian_evaluations(), single.jacobian_evaluations());
+    }
+}
+
+#[test]
+fn test_cost_tolerance() {
+    use crate::{OptimizationOptionsBuilder, ProblemBuilder};
+    let f = |x: f64| x*x - 2.0;
+    let opts = OptimizationOptionsBuilder::default()
+        .cost_tolerance(1e-6)
+        .build()
+        .unwrap();
+    let report = ProblemBuilder::<f64,_,_,_>::new(&f, 1.0)
+        .set_target_to_zero()
+        .set_weights_to_one()
+        .options(opts)
+        .build().unwrap()
+        .solve_report().unwrap();
+    assert_eq!(report.termination(), &TerminationReason::CostTolerance);
+    assert!((report.solution() - 2.0_f64.sqrt()).abs() < 1e-3);
+}
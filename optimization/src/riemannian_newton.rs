@@ -1,34 +1,122 @@
-// use math_traits::{algebra::{Affinespace, NumberOfDegreesOfFreedom, Parameters, Scalarproduct}, geometrie::Submanifold};
-// use nalgebra::DMatrix;
-// use super::{OptimizationOptions, OptimizationError};
+// newton method for minimizing a function of the ambient space over a submanifold.
+// the newton step is computed in the coordinates of the tangent space and the
+// new point is the projection of the step in the tangent space onto the manifold.
+// the hessian along the manifold is the ambient hessian on the tangent space minus the
+// riemannian_hessian_correction of the submanifold times the metric of the tangent space.
+// as this correction is a scalar the method applies to hypersurfaces like the unit sphere,
+// submanifolds of higher codimension like stiefel matrices are not supported
 
-// pub fn riemannian_newton<M:Clone+Submanifold>(f:& impl Fn(&M::AmbientSpace) -> f64,
-//                                               df:Option<impl Fn(&M::AmbientSpace) -> <M::AmbientSpace as Affinespace>::V>,
-//                                               first_guess:M,
-//                                               opts:Option<OptimizationOptions>) -> Result<M, OptimizationError<M>> 
-//     where 
-//     M::AmbientSpace : Parameters+NumberOfDegreesOfFreedom,
-//     <M::AmbientSpace as Affinespace>::V : Scalarproduct<Output=f64> {
-//     let opts: OptimizationOptions = opts.unwrap_or_default();
-//     let mut x=first_guess.clone();
-//     let mut iter:u8=0;
-//     while &iter < opts.max_iter() {
-//         let grad=|xx:&M::AmbientSpace|super::jacobian::jacobian(f, xx, Some(opts.fd().clone())).column(0);
-//         let xe=x.embedding();
-//         let gradx=grad(&xe);
-//         let mut hessx=super::jacobian::jacobian(&grad, &xe, Some(opts.fd().clone()));
-//         hessx-=x.riemannian_hessian_correction(|v|v.scalar_product(gradx))*DMatrix::identity(hessx.nrows(),hessx.ncols());
-//         // restrict to tangent space
-//         let f=|tv:&Vec<f64>|{
-//             let tv=x.tangent_space(tv)-xe;
+use num_traits::{One, Zero};
+use container_traits::{AnyParameters, FromFn, LinearContainerConstructError as LCCE};
+use container_traits::for_static::NumberOfDegreesOfFreedom;
 
-//         }
+use algebra_traits::{CastFromf64, Norm, RealNumber, Scalarproduct};
+use geometry_traits::Submanifold;
 
+use algebra::VectorDyn;
 
-//         iter+=1;
-//     }
-//     if &iter == opts.max_iter() {
-//         return Err(OptimizationError::MaximalIteration(iter));
-//     }
-//     Ok(x)
-// }
\ No newline at end of file
+use matrix::MatrixDyn;
+use crate::dvec::{dot, lin_comb, mat_vec, scale};
+use crate::quasi_newton::numerical_gradient;
+use super::{from_dvec, into_dvec, jacobian_dvec, FiniteDifference, OptimizationError, OptimizationOptions};
+
+fn newton<F : RealNumber,
+          M : Clone+Submanifold<F>>(
+    f:impl Fn(M::AmbientSpace) -> F,
+    gradient:impl Fn(&VectorDyn<F>) -> VectorDyn<F>,
+    hessian_fd:FiniteDifference<F>,
+    x0:M,
+    opts:OptimizationOptions<F>) -> Result<M, OptimizationError<F,M>>
+    where M::AmbientSpace : Clone+AnyParameters<F,LCCE>,
+          M::V            : Clone+AnyParameters<F,LCCE>+Scalarproduct<ScProdT=F> {
+    let n=<M as NumberOfDegreesOfFreedom<F>>::NDOFS;
+    let half=F::from_f64(0.5);
+    let cost=|x:&M|f(x.clone().embedding());
+    let unit=|j:usize|(0..n).map(|i|if i == j { F::one() } else { F::zero() }).collect::<Vec<F>>();
+    let mut x=x0;
+    let mut fx=cost(&x);
+    let mut iter:u8=0;
+    while &iter < opts.max_iter() {
+        let xe:VectorDyn<F>=into_dvec(x.clone().embedding());
+        let basis:Vec<VectorDyn<F>>=
+            (0..n).map(|j|into_dvec(x.clone().tangent_space(unit(j))-x.clone().embedding()))
+                  .collect();
+        let g_amb=gradient(&xe);
+        let h_amb=jacobian_dvec(|y|gradient(&y), xe, hessian_fd.clone());
+        // the position vector of x has the coordinates of x, where the gradient is g_amb
+        let correction=x.clone().riemannian_hessian_correction(|_:&M::V|from_dvec(g_amb.clone()));
+        let h_basis:Vec<VectorDyn<F>>=basis.iter().map(|t|mat_vec(&h_amb, t)).collect();
+        let h=MatrixDyn::<F>::from_fn((n,n),|(i,j)|{
+            let ambient=(dot(&basis[i], &h_basis[j])+dot(&basis[j], &h_basis[i]))*half.clone();
+            ambient-correction.clone()*dot(&basis[i], &basis[j])
+        });
+        let g=VectorDyn::from_iter(basis.iter().map(|t|dot(&g_amb, t)));
+        let newton=match crate::try_solve_least_squares(h.clone(), scale(-F::one(), &g)) {
+            Some(newton) => newton,
+            None => { return Err(OptimizationError::MatrixNotFullRank(h, x)); }
+        };
+        // steepest descent if the newton step is not a descent direction
+        let v=if dot(&newton, &g) < F::zero() { newton } else { scale(-F::one(), &g) };
+        let step=basis.iter()
+                      .zip(v.iter())
+                      .fold(VectorDyn::from_element(basis[0].len(), F::zero()),
+                            |acc,(t,vi)|lin_comb(F::one(), &acc, vi.clone(), t));
+        if &step.norm() < opts.target_cost() { break; }
+        let mut t=F::one();
+        let mut accepted=None;
+        while t > F::from_f64(1e-10) {
+            let xt=M::project(x.clone().tangent_space(v.iter().map(|vi|vi.clone()*t.clone()).collect()));
+            let ft=cost(&xt);
+            if ft < fx {
+                accepted=Some((xt, ft));
+                break;
+            }
+            t=t*half.clone();
+        }
+        match accepted {
+            Some((xt, ft)) => {
+                x=xt;
+                fx=ft;
+            },
+            // no decrease possible although the step is not yet small
+            None => { return Err(OptimizationError::NoDecrease(x)); }
+        }
+        iter+=1;
+    }
+    if &iter == opts.max_iter() {
+        return Err(OptimizationError::MaximalIteration(iter));
+    }
+    Ok(x)
+}
+
+// the hessian is computed with the finite difference of the options applied to the gradient
+pub fn riemannian_newton_with_gradient<F : RealNumber,
+                                       M : Clone+Submanifold<F>>(
+    f:impl Fn(M::AmbientSpace) -> F,
+    gradient:impl Fn(M::AmbientSpace) -> M::V,
+    x0:M,
+    opts:Option<OptimizationOptions<F>>) -> Result<M, OptimizationError<F,M>>
+    where M::AmbientSpace : Clone+AnyParameters<F,LCCE>,
+          M::V            : Clone+AnyParameters<F,LCCE>+Scalarproduct<ScProdT=F> {
+    let opts=opts.unwrap_or_default();
+    let gradient=|x:&VectorDyn<F>|into_dvec(gradient(from_dvec(x.clone())));
+    newton(f, gradient, opts.fd().clone(), x0, opts)
+}
+
+// the gradient is computed with the finite difference of the options, the hessian with
+// hessian_fd applied to the gradient. the errors of the gradient are divided by the step
+// of hessian_fd, so it has to be large compared to the step of the options.
+// see geometry::UnitSphere for an example
+pub fn riemannian_newton<F : RealNumber,
+                         M : Clone+Submanifold<F>>(
+    f:impl Fn(M::AmbientSpace) -> F,
+    x0:M,
+    hessian_fd:FiniteDifference<F>,
+    opts:Option<OptimizationOptions<F>>) -> Result<M, OptimizationError<F,M>>
+    where M::AmbientSpace : Clone+AnyParameters<F,LCCE>,
+          M::V            : Clone+AnyParameters<F,LCCE>+Scalarproduct<ScProdT=F> {
+    let opts=opts.unwrap_or_default();
+    let f_dvec=|x:&VectorDyn<F>|f(from_dvec(x.clone()));
+    let gradient=|x:&VectorDyn<F>|numerical_gradient(&f_dvec, x, &f_dvec(x), opts.fd());
+    newton(&f, gradient, hessian_fd, x0, opts)
+}
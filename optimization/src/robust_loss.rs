@@ -0,0 +1,87 @@
+use std::sync::Arc;
+use num_traits::{One, Zero};
+use algebra_traits::{CastFromf64, RealNumber, TryDiv};
+
+// loss functions which are less sensitive to outliers than the sum of squares.
+// they are represented by their weight function u -> rho'(u)/u where u is the
+// absolute value of a weighted residual divided by the robust scale estimate of
+// mad_scale, see iteratively reweighted least squares. the thresholds are hence
+// independent of the units of the residuals, usual choices are Huber(1.345),
+// Cauchy(2.385) and Tukey(4.685)
+#[derive(Clone)]
+pub enum RobustLoss<R> {
+    Huber(R),
+    Cauchy(R),
+    Tukey(R),
+    Custom(Arc<dyn Fn(R) -> R+Send+Sync>),
+}
+
+impl<R:RealNumber> RobustLoss<R> {
+    pub fn weight(&self, u:R) -> R {
+        let div=|a:R,b:R|a.try_div(b).unwrap();
+        match self {
+            RobustLoss::Huber(k) => if &u <= k { R::one() } else { div(k.clone(), u) },
+            RobustLoss::Cauchy(c) => {
+                let q=div(u, c.clone());
+                div(R::one(), R::one()+q.clone()*q)
+            },
+            RobustLoss::Tukey(c) => if &u <= c {
+                let q=div(u, c.clone());
+                let s=R::one()-q.clone()*q;
+                s.clone()*s
+            } else {
+                R::zero()
+            },
+            RobustLoss::Custom(w) => w(u),
+        }
+    }
+}
+
+// robust estimate 1.4826*median(u) of the standard deviation of residuals with absolute values u.
+// it is bounded below relative to the largest residual, such that rounding errors are not
+// mistaken for outliers if most residuals are fitted exactly. zero only if all u are zero
+pub(crate) fn mad_scale<R:RealNumber>(u:&[R]) -> R {
+    let mut sorted=u.to_vec();
+    sorted.sort_by(|a,b|a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let n=sorted.len();
+    if n == 0 {
+        return R::zero();
+    }
+    let median=if n % 2 == 1 {
+        sorted[n/2].clone()
+    } else {
+        (sorted[n/2-1].clone()+sorted[n/2].clone())*R::from_f64(0.5)
+    };
+    let s=median*R::from_f64(1.4826);
+    let floor=sorted[n-1].clone()*R::from_f64(1e-6);
+    if s < floor { floor } else { s }
+}
+
+impl<R:std::fmt::Debug> std::fmt::Debug for RobustLoss<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RobustLoss::Huber(k)  => f.debug_tuple("Huber").field(k).finish(),
+            RobustLoss::Cauchy(c) => f.debug_tuple("Cauchy").field(c).finish(),
+            RobustLoss::Tukey(c)  => f.debug_tuple("Tukey").field(c).finish(),
+            RobustLoss::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+
+#[test]
+fn test_weights() {
+    assert_eq!(RobustLoss::Huber(1.0).weight(0.5), 1.0);
+    assert_eq!(RobustLoss::Huber(1.0).weight(4.0), 0.25);
+    assert_eq!(RobustLoss::Cauchy(1.0).weight(1.0), 0.5);
+    assert_eq!(RobustLoss::Tukey(2.0).weight(3.0), 0.0);
+    assert_eq!(RobustLoss::Custom(Arc::new(|u:f64|1.0/(1.0+u))).weight(1.0), 0.5);
+}
+
+#[test]
+fn test_mad_scale() {
+    assert!((mad_scale(&[3.0, 1.0, 100.0, 2.0, 0.5]) - 1.4826*2.0).abs() < 1e-12);
+    assert!((mad_scale(&[1.0, 3.0]) - 1.4826*2.0).abs() < 1e-12);
+    // an exact fit of all but one residual
+    assert!((mad_scale(&[0.0, 0.0, 0.0, 20.0]) - 20.0e-6).abs() < 1e-18);
+    assert_eq!(mad_scale(&[0.0, 0.0]), 0.0);
+}
@@ -0,0 +1,216 @@
+// minimization of a function of one variable over an interval,
+// the variable can be any type which is represented by a single real parameter
+
+use std::cell::Cell;
+use num_traits::Zero;
+use algebra_traits::{CastFromf64, Interval, Nonnegative, Norm, RealNumber, TryDiv, TrySqrt};
+use container_traits::{FromParameter, IntoParameter};
+
+use super::OptimizationError;
+
+#[derive(Clone, Debug, derive_getters::Getters)]
+pub struct ScalarMinimum<X,R> {
+    minimizer: X,
+    minimum: R,
+    evaluations: usize,
+}
+
+fn finish<X:FromParameter<R>,R>(p:R, minimum:R, evaluations:&Cell<usize>) -> ScalarMinimum<X,R> {
+    ScalarMinimum { minimizer: X::from_parameter(p), minimum, evaluations: evaluations.get() }
+}
+
+fn abs<R:RealNumber>(r:R) -> R {
+    r.norm().into_signed()
+}
+
+fn half<R:RealNumber>(r:R) -> R {
+    r*R::from_f64(0.5)
+}
+
+// (3-sqrt(5))/2, ratio of the smaller part to the whole interval
+fn golden_ratio_complement<R:RealNumber>() -> R {
+    half(R::from_f64(3.0)-R::from_f64(5.0).try_sqrt().unwrap().into_signed())
+}
+
+// minimizes unimodal f until the bracketing interval is smaller than tol
+pub fn golden_section<R : RealNumber,
+                      X : Clone+IntoParameter<R>+FromParameter<R>>(
+    f:impl Fn(X) -> R,
+    interval:Interval<X>,
+    tol:Nonnegative<R>,
+    maxiter:Option<u8>) -> Result<ScalarMinimum<X,R>, OptimizationError<(), X>> {
+    let maxiter=maxiter.unwrap_or(100);
+    let tol=tol.into_signed();
+    let evaluations=Cell::new(0);
+    let f=|p:R|{
+        evaluations.set(evaluations.get()+1);
+        f(X::from_parameter(p))
+    };
+    let g=golden_ratio_complement::<R>();
+    let mut a=interval.lb().clone().into_parameter();
+    let mut b=interval.ub().clone().into_parameter();
+    let mut c=a.clone()+g.clone()*(b.clone()-a.clone());
+    let mut d=b.clone()-g.clone()*(b.clone()-a.clone());
+    let mut fc=f(c.clone());
+    let mut fd=f(d.clone());
+    let mut iter=0;
+    while abs(b.clone()-a.clone()) > tol {
+        if iter == maxiter {
+            return Err(OptimizationError::MaximalIteration(iter));
+        }
+        if fc < fd {
+            b=d;
+            d=c;
+            fd=fc;
+            c=a.clone()+g.clone()*(b.clone()-a.clone());
+            fc=f(c.clone());
+        } else {
+            a=c;
+            c=d;
+            fc=fd;
+            d=b.clone()-g.clone()*(b.clone()-a.clone());
+            fd=f(d.clone());
+        }
+        iter+=1;
+    }
+    Ok(if fc < fd { finish(c, fc, &evaluations) } else { finish(d, fd, &evaluations) })
+}
+
+// brent's method combines parabolic interpolation with golden section steps,
+// it needs far fewer evaluations than golden section for smooth f
+pub fn brent<R : RealNumber,
+             X : Clone+IntoParameter<R>+FromParameter<R>>(
+    f:impl Fn(X) -> R,
+    interval:Interval<X>,
+    tol:Nonnegative<R>,
+    maxiter:Option<u8>) -> Result<ScalarMinimum<X,R>, OptimizationError<(), X>> {
+    let maxiter=maxiter.unwrap_or(100);
+    let tol=tol.into_signed();
+    // square root of the machine precision
+    let eps=R::from_f64(1.5e-8);
+    let third=|r:R|r.try_div(R::from_f64(3.0)).unwrap();
+    let evaluations=Cell::new(0);
+    let f=|p:R|{
+        evaluations.set(evaluations.get()+1);
+        f(X::from_parameter(p))
+    };
+    let g=golden_ratio_complement::<R>();
+    let mut a=interval.lb().clone().into_parameter();
+    let mut b=interval.ub().clone().into_parameter();
+    // x is the best point so far, w the second best and v the previous value of w
+    let mut x=a.clone()+g.clone()*(b.clone()-a.clone());
+    let mut fx=f(x.clone());
+    let (mut w, mut fw)=(x.clone(), fx.clone());
+    let (mut v, mut fv)=(x.clone(), fx.clone());
+    // d is the current step and e the step before the last one
+    let mut d=R::zero();
+    let mut e=R::zero();
+    let mut iter=0;
+    loop {
+        let m=half(a.clone()+b.clone());
+        let tol1=eps.clone()*abs(x.clone())+third(tol.clone());
+        let tol2=tol1.clone()*R::from_f64(2.0);
+        if abs(x.clone()-m.clone()) <= tol2.clone()-half(b.clone()-a.clone()) {
+            break;
+        }
+        if iter == maxiter {
+            return Err(OptimizationError::MaximalIteration(iter));
+        }
+        let mut golden=true;
+        if abs(e.clone()) > tol1 {
+            // parabola through x, w and v
+            let r=(x.clone()-w.clone())*(fx.clone()-fv.clone());
+            let q=(x.clone()-v.clone())*(fx.clone()-fw.clone());
+            let mut p=(x.clone()-v.clone())*q.clone()-(x.clone()-w.clone())*r.clone();
+            let mut q=(q-r)*R::from_f64(2.0);
+            if q > R::zero() {
+                p=-p;
+            } else {
+                q=-q;
+            }
+            let eold=e.clone();
+            e=d.clone();
+            if abs(p.clone()) < abs(half(q.clone()*eold))
+               && p > q.clone()*(a.clone()-x.clone())
+               && p < q.clone()*(b.clone()-x.clone()) {
+                d=p.try_div(q).unwrap();
+                let u=x.clone()+d.clone();
+                // f must not be evaluated too close to the interval bounds
+                if u.clone()-a.clone() < tol2 || b.clone()-u < tol2 {
+                    d=if x < m { tol1.clone() } else { -tol1.clone() };
+                }
+                golden=false;
+            }
+        }
+        if golden {
+            e=if x < m { b.clone()-x.clone() } else { a.clone()-x.clone() };
+            d=g.clone()*e.clone();
+        }
+        // f must not be evaluated too close to x
+        let u=if abs(d.clone()) >= tol1 {
+            x.clone()+d.clone()
+        } else if d > R::zero() {
+            x.clone()+tol1
+        } else {
+            x.clone()-tol1
+        };
+        let fu=f(u.clone());
+        if fu <= fx {
+            if u < x { b=x.clone(); } else { a=x.clone(); }
+            (v, fv)=(w, fw);
+            (w, fw)=(x, fx);
+            (x, fx)=(u, fu);
+        } else {
+            if u < x { a=u.clone(); } else { b=u.clone(); }
+            if fu <= fw || w == x {
+                (v, fv)=(w, fw);
+                (w, fw)=(u, fu);
+            } else if fu <= fv || v == x || v == w {
+                (v, fv)=(u, fu);
+            }
+        }
+        iter+=1;
+    }
+    Ok(finish(x, fx, &evaluations))
+}
+
+#[cfg(test)]
+fn tolerance() -> Nonnegative<f64> {
+    Nonnegative::try_new(1e-8).unwrap()
+}
+
+#[test]
+fn test_golden_section_and_brent() {
+    let f = |x: f64| (x-2.0)*(x-2.0)+1.0;
+    let interval=Interval::try_new(0.0, 5.0).unwrap();
+    let gs=golden_section(f, interval.clone(), tolerance(), None).unwrap();
+    let br=brent(f, interval, tolerance(), None).unwrap();
+    for res in [&gs, &br] {
+        assert!((res.minimizer() - 2.0).abs() < 1e-6);
+        assert!((res.minimum() - 1.0).abs() < 1e-12);
+    }
+    // parabolic interpolation is exact for quadratic functions
+    assert!(br.evaluations() < gs.evaluations());
+}
+
+#[test]
+fn test_brent_non_smooth() {
+    let f = |x: f64| (x-0.3).abs();
+    let res=brent(f, Interval::try_new(-1.0, 1.0).unwrap(), tolerance(), None).unwrap();
+    assert!((res.minimizer() - 0.3).abs() < 1e-6);
+}
+
+#[test]
+fn test_minimize_physical_quantities() {
+    use phys_units::{Angle, Length, Meters, Radians};
+    let f = |l: Length| (l.m()-1.5)*(l.m()-1.5);
+    let interval=Interval::try_new(Length::from_m(0.0), Length::from_m(3.0)).unwrap();
+    let res=brent(f, interval, tolerance(), None).unwrap();
+    assert!((res.minimizer().m() - 1.5).abs() < 1e-6);
+
+    let f = |a: Angle| -a.rad().cos();
+    let interval=Interval::try_new(Angle::from_rad(-1.0), Angle::from_rad(2.0)).unwrap();
+    let res=golden_section(f, interval, tolerance(), None).unwrap();
+    assert!(res.minimizer().rad().abs() < 1e-6);
+    assert!((res.minimum() + 1.0).abs() < 1e-12);
+}
@@ -0,0 +1,171 @@
+// jacobians with a known sparsity pattern. columns which have no nonzero in a common row
+// are structurally independent and can be perturbed together, such that the number of
+// evaluations is the number of groups instead of the number of parameters
+use num_traits::{One, Zero};
+use container_traits::{AnyParameters, ContainerConstructError, FromFn, Get, IntoParameters, Len};
+
+use algebra_traits::{Scalar, TryDiv};
+
+use algebra::VectorDyn;
+
+use container::ContainerSparse;
+use matrix::MatrixDyn;
+use crate::dvec::lin_comb;
+use super::{from_dvec, into_dvec, FiniteDifference};
+
+// the rows of the structural nonzeros for every column
+fn rows_of_cols(pattern:&ContainerSparse<(usize,usize),bool>) -> Vec<Vec<usize>> {
+    let (bm, default, (nrows, ncols))=pattern.clone().into_parts();
+    let mut rows=vec![Vec::new(); ncols];
+    if default {
+        for i in 0..nrows {
+            for (j, rows_j) in rows.iter_mut().enumerate() {
+                if *bm.get(&(i,j)).unwrap_or(&true) {
+                    rows_j.push(i);
+                }
+            }
+        }
+    } else {
+        for ((i,j),_) in bm.into_iter().filter(|(_,nonzero)|*nonzero) {
+            rows[j].push(i);
+        }
+    }
+    rows
+}
+
+// greedy coloring of the column intersection graph, the columns are colored in
+// the order of decreasing number of nonzeros. returns the columns of every color
+pub fn column_groups(pattern:&ContainerSparse<(usize,usize),bool>) -> Vec<Vec<usize>> {
+    let (nrows, ncols)=pattern.clone().into_parts().2;
+    let rows=rows_of_cols(pattern);
+    let mut cols_of_rows=vec![Vec::new(); nrows];
+    for (j, rows_j) in rows.iter().enumerate() {
+        for i in rows_j {
+            cols_of_rows[*i].push(j);
+        }
+    }
+    let mut order:Vec<usize>=(0..ncols).collect();
+    order.sort_by(|a,b|rows[*b].len().cmp(&rows[*a].len()));
+    let mut colors:Vec<Option<usize>>=vec![None; ncols];
+    let mut groups:Vec<Vec<usize>>=Vec::new();
+    for j in order {
+        let mut forbidden=vec![false; groups.len()];
+        for i in rows[j].iter() {
+            for k in cols_of_rows[*i].iter() {
+                if let Some(c) = colors[*k] {
+                    forbidden[c]=true;
+                }
+            }
+        }
+        let c=forbidden.iter()
+                       .position(|f|!f)
+                       .unwrap_or(groups.len());
+        if c == groups.len() {
+            groups.push(Vec::new());
+        }
+        groups[c].push(j);
+        colors[j]=Some(c);
+    }
+    groups
+}
+
+// the columns of a group are perturbed along the direction d with d_j=step(x_j)/step(1),
+// the entries of the jacobian are obtained from the directional derivative J*d
+pub fn sparse_jacobian_dvec<F:Scalar>(
+    f: impl Fn(VectorDyn<F>) -> VectorDyn<F>,
+    x0: VectorDyn<F>,
+    pattern: &ContainerSparse<(usize,usize),bool>,
+    fin_diff: FiniteDifference<F>,
+) -> ContainerSparse<(usize,usize),F> {
+    let size=pattern.clone().into_parts().2;
+    let rows=rows_of_cols(pattern);
+    let fx0=f(x0.clone());
+    let unit_step=fin_diff.step_at(&F::one());
+    let scales:Vec<F>=x0.iter()
+                        .map(|xj|fin_diff.step_at(xj).try_div(unit_step.clone()).unwrap())
+                        .collect();
+    let mut jac=ContainerSparse::new(F::zero(), size);
+    for group in column_groups(pattern) {
+        let d=VectorDyn::from_iter((0..x0.len()).map(|j|if group.contains(&j) { scales[j].clone() } else { F::zero() }));
+        let jd=fin_diff.apply(|t:F|f(lin_comb(F::one(), &x0, t, &d)), &fx0, &F::one());
+        for j in group {
+            for i in rows[j].iter() {
+                jac.insert((*i,j), jd[*i].clone().try_div(scales[j].clone()).unwrap()).unwrap();
+            }
+        }
+    }
+    jac
+}
+
+pub fn sparse_jacobian<F : Scalar,
+                       X : Clone+AnyParameters<F,ContainerConstructError<usize>>,
+                       Y : Clone+IntoParameters<F>>(
+    f: impl Fn(X) -> Y,
+    x0: X,
+    pattern: &ContainerSparse<(usize,usize),bool>,
+    fin_diff: FiniteDifference<F>,
+) -> ContainerSparse<(usize,usize),F> {
+    let f = |dvec: VectorDyn<F>| into_dvec::<F,Y>(f(from_dvec::<F,X>(dvec)));
+    sparse_jacobian_dvec(f, into_dvec::<F,X>(x0), pattern, fin_diff)
+}
+
+// the least squares solvers of this crate take dense jacobians, see Problem::solve_with_sparsity
+pub fn into_dense<F:Scalar>(jac:&ContainerSparse<(usize,usize),F>) -> MatrixDyn<F> {
+    let size=jac.clone().into_parts().2;
+    MatrixDyn::from_fn(size,|ij|jac.get(ij).unwrap().clone())
+}
+
+#[cfg(test)]
+fn tridiagonal_pattern(n:usize) -> ContainerSparse<(usize,usize),bool> {
+    let mut pattern=ContainerSparse::new(false, (n,n));
+    for i in 0..n {
+        for j in i.saturating_sub(1)..(i+2).min(n) {
+            pattern.insert((i,j), true).unwrap();
+        }
+    }
+    pattern
+}
+
+#[cfg(test)]
+fn tridiagonal_function(x:VectorDyn<f64>) -> VectorDyn<f64> {
+    let n=x.len();
+    VectorDyn::from_iter((0..n).map(|i|{
+        let left=if i > 0 { x[i-1].sin() } else { 1.0 };
+        let right=if i+1 < n { x[i+1] } else { 0.0 };
+        x[i]*x[i]+left*right
+    }))
+}
+
+#[test]
+fn test_column_groups_tridiagonal() {
+    let groups=column_groups(&tridiagonal_pattern(10));
+    assert_eq!(groups.len(), 3);
+    for group in groups {
+        for w in group.windows(2) {
+            assert!(w[0].abs_diff(w[1]) >= 3);
+        }
+    }
+}
+
+#[test]
+fn test_sparse_jacobian_tridiagonal() {
+    use crate::jacobian_dvec;
+    use std::cell::Cell;
+    let n=10;
+    let x0=VectorDyn::from_iter((0..n).map(|i|0.1*i as f64-0.3));
+    let evaluations=Cell::new(0);
+    let f=|x:VectorDyn<f64>|{
+        evaluations.set(evaluations.get()+1);
+        tridiagonal_function(x)
+    };
+    let sparse=sparse_jacobian_dvec(f, x0.clone(), &tridiagonal_pattern(n), FiniteDifference::default());
+    // one evaluation at x0 and two for every group
+    assert_eq!(evaluations.get(), 7);
+    let dense=jacobian_dvec(tridiagonal_function, x0, FiniteDifference::default());
+    let sparse=into_dense(&sparse);
+    for i in 0..n {
+        for j in 0..n {
+            assert!((sparse[(i,j)] - dense[(i,j)]).abs() < 1e-6);
+        }
+    }
+}